@@ -3,6 +3,7 @@ extern crate static_assertions;
 use proc_macro::TokenStream;
 
 mod mem;
+mod size;
 
 /// Use the "aligns" or "size" options to ensure memory and storage safety with state structs or enums.
 ///
@@ -12,12 +13,32 @@ mod mem;
 ///
 /// *size: usize*: Enforces that the struct is a specific size
 ///
+/// *borsh = usize*: Enforces the largest borsh serialized size of the struct, which
+///                  must derive `MaxSerializedSize`
+///
 /// For example, decorate a struct with any of these attributes:
 /// #[assert_size(128, aligns)
 /// #[assert_size(128)
 /// #[assert_size(aligns)
 /// #[assert_size(aligns, 128)
+/// #[assert_size(borsh = 88)
 #[proc_macro_attribute]
 pub fn assert_size(args: TokenStream, input_struct: TokenStream) -> TokenStream {
     mem::handler(args, input_struct)
 }
+
+/// Derives a `MAX_SERIALIZED_SIZE` const with the largest borsh encoded size of the
+/// struct or enum, to size accounts from.
+///
+/// `String` and `Vec` fields need their maximum number of elements:
+/// #[max_len(MAX_SYMBOL_LENGTH)]
+/// pub symbol: String,
+///
+/// Fields of other types must derive `MaxSerializedSize` themselves.
+///
+/// Structs with named fields also get a `<FIELD>_MAX_OFFSET` const per field, the
+/// field's offset when every field before it takes its largest size.
+#[proc_macro_derive(MaxSerializedSize, attributes(max_len))]
+pub fn max_serialized_size(input: TokenStream) -> TokenStream {
+    size::handler(input)
+}
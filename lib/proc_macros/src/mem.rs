@@ -46,6 +46,7 @@ use keep_trying;
 enum Constraint {
     Aligns,
     Size(usize),
+    Borsh(usize),
 }
 
 fn to_token(constraint: &Constraint, name: &proc_macro2::Ident) -> proc_macro2::TokenStream {
@@ -56,6 +57,9 @@ fn to_token(constraint: &Constraint, name: &proc_macro2::Ident) -> proc_macro2::
         Constraint::Size(size) => quote! {
             static_assertions::const_assert_eq!(#size, std::mem::size_of::<#name>());
         },
+        Constraint::Borsh(size) => quote! {
+            static_assertions::const_assert_eq!(#size, #name::MAX_SERIALIZED_SIZE);
+        },
     }
 }
 
@@ -72,6 +76,11 @@ fn parse_args(args: &TokenStream) -> Vec<Constraint> {
                 .collect();
             if standarg == "aligns" {
                 Constraint::Aligns
+            } else if let Some(size) = standarg.strip_prefix("borsh=") {
+                Constraint::Borsh(
+                    size.parse::<usize>()
+                        .unwrap_or_else(|_| panic!("Invalid borsh size: {}", arg)),
+                )
             } else if let Ok(size) = standarg.parse::<usize>() {
                 Constraint::Size(size)
             } else {
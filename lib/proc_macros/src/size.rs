@@ -0,0 +1,119 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Expr, Fields, GenericArgument, PathArguments,
+    Type,
+};
+
+pub fn handler(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let ident = &input.ident;
+    let mut offsets = vec![];
+    let size = match &input.data {
+        Data::Struct(data) => {
+            let mut offset = quote! { 0 };
+            for field in data.fields.iter() {
+                let size = type_size(&field.ty, max_len(&field.attrs).as_ref());
+                if let Some(field_ident) = &field.ident {
+                    let name =
+                        format_ident!("{}_MAX_OFFSET", field_ident.to_string().to_uppercase());
+                    offsets.push(quote! { pub const #name: usize = #offset; });
+                }
+                offset = quote! { #offset + #size };
+            }
+            offset
+        }
+        Data::Enum(data) => {
+            // Borsh writes the variant index as a single byte
+            let variants = data
+                .variants
+                .iter()
+                .map(|variant| fields_size(&variant.fields))
+                .collect::<Vec<_>>();
+            let largest = variants
+                .into_iter()
+                .reduce(|a, b| quote! { { let a = #a; let b = #b; if a > b { a } else { b } } })
+                .unwrap_or_else(|| quote! { 0 });
+            quote! { 1 + #largest }
+        }
+        Data::Union(_) => panic!("MaxSerializedSize can't be derived for unions"),
+    };
+    let tokens = quote! {
+        impl #ident {
+            /// Largest number of bytes the borsh serialization can take.
+            pub const MAX_SERIALIZED_SIZE: usize = #size;
+            #(#offsets)*
+        }
+    };
+    TokenStream::from(tokens)
+}
+
+fn fields_size(fields: &Fields) -> proc_macro2::TokenStream {
+    let sizes = fields
+        .iter()
+        .map(|field| type_size(&field.ty, max_len(&field.attrs).as_ref()))
+        .collect::<Vec<_>>();
+    quote! { 0 #(+ #sizes)* }
+}
+
+fn max_len(attrs: &[Attribute]) -> Option<Expr> {
+    attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("max_len"))
+        .map(|attr| {
+            attr.parse_args::<Expr>()
+                .expect("max_len takes the maximum number of elements, e.g. #[max_len(10)]")
+        })
+}
+
+fn type_size(ty: &Type, max_len: Option<&Expr>) -> proc_macro2::TokenStream {
+    match ty {
+        Type::Array(array) => {
+            let elem = type_size(&array.elem, max_len);
+            let len = &array.len;
+            quote! { (#len) * (#elem) }
+        }
+        Type::Tuple(tuple) => {
+            let elems = tuple.elems.iter().map(|elem| type_size(elem, max_len));
+            quote! { 0 #(+ #elems)* }
+        }
+        Type::Path(path) => {
+            let segment = path.path.segments.last().expect("Empty type path");
+            let name = segment.ident.to_string();
+            match name.as_str() {
+                "u8" | "i8" | "bool" => quote! { 1 },
+                "u16" | "i16" => quote! { 2 },
+                "u32" | "i32" | "f32" => quote! { 4 },
+                "u64" | "i64" | "f64" => quote! { 8 },
+                "u128" | "i128" => quote! { 16 },
+                "Pubkey" => quote! { 32 },
+                "String" => {
+                    let max_len = max_len.expect("String fields need #[max_len(..)]");
+                    quote! { 4 + (#max_len) }
+                }
+                "Option" => {
+                    let inner = type_size(generic_argument(&segment.arguments), max_len);
+                    quote! { 1 + #inner }
+                }
+                "Vec" => {
+                    let max_len = max_len.expect("Vec fields need #[max_len(..)]");
+                    let inner = type_size(generic_argument(&segment.arguments), None);
+                    quote! { 4 + (#max_len) * (#inner) }
+                }
+                "Box" => type_size(generic_argument(&segment.arguments), max_len),
+                _ => quote! { <#path>::MAX_SERIALIZED_SIZE },
+            }
+        }
+        _ => panic!("MaxSerializedSize doesn't support this field type"),
+    }
+}
+
+fn generic_argument(arguments: &PathArguments) -> &Type {
+    match arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first() {
+            Some(GenericArgument::Type(ty)) => ty,
+            _ => panic!("Expected a type argument"),
+        },
+        _ => panic!("Expected a type argument"),
+    }
+}
@@ -0,0 +1,92 @@
+use borsh::BorshSerialize;
+use proc_macros::MaxSerializedSize;
+
+const MAX_NAME_LENGTH: usize = 6;
+const MAX_ITEMS: usize = 3;
+
+#[derive(BorshSerialize, Clone, MaxSerializedSize)]
+struct Pubkey([u8; 32]);
+
+#[derive(BorshSerialize, Clone, MaxSerializedSize)]
+enum Kind {
+    Empty,
+    Amount(u64),
+    Owner { index: u16, owner: Pubkey },
+}
+
+#[derive(BorshSerialize, Clone, MaxSerializedSize)]
+struct Item {
+    limit: Option<u64>,
+    kind: Kind,
+}
+
+#[derive(BorshSerialize, MaxSerializedSize)]
+struct Account {
+    #[max_len(MAX_NAME_LENGTH)]
+    name: String,
+    #[max_len(MAX_ITEMS)]
+    items: Vec<Item>,
+    delegate: Option<Pubkey>,
+    seed: [u8; 4],
+    #[max_len(2)]
+    tiers: Option<Vec<u16>>,
+    flag: bool,
+}
+
+fn largest_item() -> Item {
+    Item {
+        limit: Some(u64::MAX),
+        kind: Kind::Owner {
+            index: 1,
+            owner: Pubkey([1; 32]),
+        },
+    }
+}
+
+fn largest_account() -> Account {
+    Account {
+        name: "a".repeat(MAX_NAME_LENGTH),
+        items: vec![largest_item(); MAX_ITEMS],
+        delegate: Some(Pubkey([2; 32])),
+        seed: [3; 4],
+        tiers: Some(vec![4; 2]),
+        flag: true,
+    }
+}
+
+#[test]
+fn enum_takes_its_largest_variant() {
+    assert_eq!(Kind::MAX_SERIALIZED_SIZE, 1 + 2 + 32);
+    let owner = Kind::Owner {
+        index: 1,
+        owner: Pubkey([1; 32]),
+    };
+    assert_eq!(owner.try_to_vec().unwrap().len(), Kind::MAX_SERIALIZED_SIZE);
+    assert!(Kind::Empty.try_to_vec().unwrap().len() < Kind::MAX_SERIALIZED_SIZE);
+}
+
+#[test]
+fn matches_the_borsh_size_of_the_largest_value() {
+    assert_eq!(Item::MAX_SERIALIZED_SIZE, 9 + 35);
+    assert_eq!(
+        Account::MAX_SERIALIZED_SIZE,
+        (4 + 6) + (4 + 3 * 44) + 33 + 4 + (1 + 4 + 2 * 2) + 1
+    );
+    assert_eq!(
+        largest_account().try_to_vec().unwrap().len(),
+        Account::MAX_SERIALIZED_SIZE
+    );
+}
+
+#[test]
+fn offsets_assume_every_earlier_field_is_largest() {
+    assert_eq!(Account::NAME_MAX_OFFSET, 0);
+    assert_eq!(Account::ITEMS_MAX_OFFSET, 10);
+    assert_eq!(Account::DELEGATE_MAX_OFFSET, 10 + 136);
+    assert_eq!(Account::SEED_MAX_OFFSET, 10 + 136 + 33);
+    assert_eq!(Item::KIND_MAX_OFFSET, 9);
+
+    let data = largest_account().try_to_vec().unwrap();
+    let seed = Account::SEED_MAX_OFFSET;
+    assert_eq!(&data[seed..seed + 4], &[3; 4]);
+}
@@ -3,22 +3,57 @@ use mpl_token_metadata::state::{
 };
 use solana_program::pubkey::Pubkey;
 
+use crate::{ConfigLineTemplate, ConfigPage, ConfigPageSettings, Tars};
+
 pub const TARS_CREATOR_WALLET: &str = "Bi4UpEtKxnHwCw7b9xkMCouGT6xLNm8nixs2fTmxTevs";
 
 pub const EXPIRE_OFFSET: i64 = 10 * 60;
 pub const BLOCK_HASHES: Pubkey =
     solana_program::pubkey!("SysvarRecentB1ockHashes11111111111111111111");
 pub const BOT_FEE: u64 = 10000000;
+pub const WHITELIST_CLEANUP_TIP: u64 = 5000;
 pub const PREFIX: &str = "tars";
 pub const COLLECTIONS_FEATURE_INDEX: usize = 0;
+// Bits of `Tars::features`, the collections flag above is kept in the uuid
+pub const BLOCKLIST_FEATURE_INDEX: usize = 1;
+pub const CONFIG_LOCKED_FEATURE_INDEX: usize = 3;
+pub const REVEAL_FEATURE_INDEX: usize = 4;
+pub const STAGED_REVEAL_FEATURE_INDEX: usize = 5;
 pub const CONFIG_LINE_SIZE: usize = 4 + MAX_NAME_LENGTH + 4 + MAX_URI_LENGTH;
 pub const COLLECTION_PDA_SIZE: usize = 8 + 64;
+pub const MAX_REVEAL_STAGES: usize = 8;
+/// Start of the bitmasks of a config page, its config lines follow them.
+pub const CONFIG_PAGE_MASKS_START: usize = 8 + ConfigPage::MAX_SERIALIZED_SIZE;
+/// Tars using config pages keep the count of loaded lines at `CONFIG_ARRAY_START`
+/// like in-account lines, then the page being minted and a bitmask of the pages
+/// minting was started on.
+pub const CONFIG_PAGE_CURSOR_START: usize = CONFIG_ARRAY_START + 4;
+pub const USED_CONFIG_PAGES_START: usize = CONFIG_PAGE_CURSOR_START + 4;
+pub const MAX_GATEKEEPER_NETWORKS: usize = 3;
+pub const MAX_HOLDER_DISCOUNTS: usize = 4;
 pub const GUMDROP_ID: Pubkey =
     solana_program::pubkey!("gdrpGjVffourzkdDRrQmySw4aTHr8a3xmQzzxSwFD1a");
 pub const CUPCAKE_ID: Pubkey =
     solana_program::pubkey!("cakeGJxEdGpZ3MJP8sM3QypwzuzZpko1ueonUQgKLPE");
 pub const A_TOKEN: Pubkey = solana_program::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
-pub const CONFIG_ARRAY_START: usize = 8 + // key
+/// Layout version written to new and migrated tars accounts.
+pub const TARS_VERSION: u8 = 3;
+pub const UUID_LENGTH: usize = 6;
+/// Start of the config lines, right after the largest possible tars header.
+pub const CONFIG_ARRAY_START: usize = 8 + Tars::MAX_SERIALIZED_SIZE;
+// Fields added after `features` must be reviewed against the config array start
+static_assertions::const_assert_eq!(CONFIG_ARRAY_START, 8 + Tars::FEATURES_MAX_OFFSET + 4);
+/// Start of the config lines of version 2 accounts, which kept their feature
+/// flags in the uuid, kept to migrate them.
+pub const V2_CONFIG_ARRAY_START: usize =
+    CONFIG_ARRAY_START - 4 - (1 + ConfigPageSettings::MAX_SERIALIZED_SIZE);
+/// Start of the config lines of version 1 accounts, from before config line
+/// templates, kept to migrate them.
+pub const V1_CONFIG_ARRAY_START: usize =
+    V2_CONFIG_ARRAY_START - (1 + ConfigLineTemplate::MAX_SERIALIZED_SIZE);
+/// Start of the config lines of accounts deployed before the layout was
+/// versioned, kept to migrate them.
+pub const LEGACY_CONFIG_ARRAY_START: usize = 8 + // key
     32 + // authority
     32 + //wallet
     33 + // token mint
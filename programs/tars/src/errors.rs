@@ -94,5 +94,146 @@ pub enum TarsError {
     NoWhitelistSpots,
     #[msg("Magic hat is not live for WL!")]
     TarsNotLiveForWL,
+    #[msg("Number of whitelist entries does not match the number of accounts passed")]
+    WhitelistEntriesMismatch,
+    #[msg("Wallet is already whitelisted")]
+    WalletAlreadyWhitelisted,
+    #[msg("WL mint has ended for this tier.")]
+    WLMintEnded,
+    #[msg("Whitelist account still has spots and its tier has not ended.")]
+    WhitelistAccountStillActive,
+    #[msg("Missing whitelist delegation when minting as a delegate")]
+    WhitelistDelegationMissing,
+    #[msg("Signer is not the delegate of this whitelist delegation")]
+    InvalidWhitelistDelegate,
+    #[msg("Token mint to failed")]
+    TokenMintToFailed,
+    #[msg("Tars has no whitelist mint settings")]
+    NoWhitelistMintSettings,
+    #[msg("Raffle registration must end after it starts and before settlement ends")]
+    InvalidRaffleSchedule,
+    #[msg("Raffle registration is closed")]
+    RaffleRegistrationClosed,
+    #[msg("Raffle registration is still open")]
+    RaffleRegistrationOpen,
+    #[msg("Raffle has already been drawn")]
+    RaffleAlreadyDrawn,
+    #[msg("Raffle has not been drawn yet")]
+    RaffleNotDrawn,
+    #[msg("Raffle still has unsettled tickets")]
+    RaffleNotSettled,
+    #[msg("Missing NFT of the required collection")]
+    MissingCollectionNft,
+    #[msg("NFT is not a verified member of the required collection")]
+    InvalidCollectionNft,
+    #[msg("NFT has no uses left for this tars")]
+    NftUsesExhausted,
+    #[msg("Too many holder discounts")]
+    TooManyHolderDiscounts,
+    #[msg("Holder discount percentage must be at most 100")]
+    InvalidHolderDiscount,
+    #[msg("Missing mint voucher signed by the authorized signer")]
+    MissingMintVoucher,
+    #[msg("Mint voucher is invalid for this mint")]
+    InvalidMintVoucher,
+    #[msg("Mint voucher has expired")]
+    MintVoucherExpired,
+    #[msg("Mint voucher has no uses left")]
+    MintVoucherExhausted,
+    #[msg("Blocklist has no room for more wallets")]
+    BlocklistFull,
+    #[msg("Missing blocklist of the tars")]
+    MissingBlocklist,
+    #[msg("Wallet is blocked from minting")]
+    WalletBlocked,
+    #[msg("Wallet has to wait longer before minting again")]
+    MintCooldownActive,
+    #[msg("Maximum number of mints in this slot reached")]
+    SlotMintLimitReached,
+    #[msg("Missing wallet mint record for the mint cooldown")]
+    MissingWalletMintRecord,
+    #[msg("Gatekeeper needs between one and the maximum number of networks")]
+    InvalidGatekeeperSettings,
+    #[msg("Sale has ended, its end date has passed")]
+    SaleEndedByDate,
+    #[msg("Sale has ended, its maximum number of items were redeemed")]
+    SaleEndedByAmount,
+    #[msg("Sale has ended, its maximum proceeds were raised")]
+    SaleEndedByProceeds,
+    #[msg("Tars account layout is outdated, migrate it first")]
+    TarsVersionMismatch,
+    #[msg("Tars account is already on the current layout")]
+    TarsAlreadyMigrated,
+    #[msg("Cannot remove config lines once minting has started")]
+    CannotShrinkDuringMint,
+    #[msg("Account can grow by at most 10240 bytes per resize")]
+    ResizeTooLarge,
+    #[msg("Tars does not have all its config lines loaded")]
+    TarsNotFullyLoaded,
+    #[msg("Tars does not use config pages")]
+    ConfigPagesNotEnabled,
+    #[msg("Missing config page of the tars")]
+    MissingConfigPage,
+    #[msg("Config page does not belong to the tars or is out of order")]
+    InvalidConfigPage,
+    #[msg("Config line template prefixes and suffixes must fit the name and uri")]
+    InvalidConfigLineTemplate,
+    #[msg("Config line name or uri is too long")]
+    ConfigLineTooLong,
+    #[msg("Cannot change the size of config lines")]
+    CannotChangeConfigLineSize,
+    #[msg("Config lines are locked")]
+    ConfigLocked,
+    #[msg("Config lines must be locked first")]
+    ConfigNotLocked,
+    #[msg("Config line was never added")]
+    ConfigLineNotLoaded,
+    #[msg("Reveals need hidden settings")]
+    RevealRequiresHiddenSettings,
+    #[msg("Reveals need retain authority to be true")]
+    RevealRequiresRetainAuthority,
+    #[msg("Tars has no committed reveal")]
+    RevealNotCommitted,
+    #[msg("NFT was not minted by the tars as this mint number or is already revealed")]
+    NftNotRevealable,
+    #[msg("Reveal proof does not match the committed root")]
+    InvalidRevealProof,
+    #[msg("Reveal stages must be ordered by their end and unlock time, cover every item and fit the maximum")]
+    InvalidRevealStages,
+    #[msg("Missing reveal schedule of the tars")]
+    MissingRevealSchedule,
+    #[msg("Reveal stage of this mint number is not unlocked yet")]
+    RevealStageLocked,
+    #[msg("Missing mint receipt or metadata of an NFT to reveal")]
+    MissingRevealMetadata,
+    #[msg("Raffle draw slot is already fixed")]
+    RaffleDrawScheduled,
+    #[msg("Raffle registration must be closed before the draw")]
+    RaffleDrawNotScheduled,
+    #[msg("Raffle draw slot has not passed yet")]
+    RaffleDrawSlotPending,
+    #[msg("Hash of the raffle draw slot is not in the slot hashes")]
+    RaffleDrawSlotHashMissing,
+    #[msg("Raffle of this ticket is still open")]
+    RaffleNotClosed,
+    #[msg("Tars reveals in stages from its reveal schedule")]
+    RevealScheduleCommitted,
+    #[msg("Whitelist account is already on the current layout")]
+    WhitelistAlreadyMigrated,
+    #[msg("Missing NFT to burn for the mint")]
+    MissingBurnNft,
+    #[msg("Burn-to-mint needs at least one NFT to burn")]
+    InvalidBurnToMintSettings,
+    #[msg("Config pages must hold at least one line and fit in an account the program can create")]
+    InvalidConfigPageSettings,
+    #[msg("Config page settings can't change")]
+    CannotChangeConfigPages,
+    #[msg("Missing mint receipt of the NFT")]
+    MissingMintReceipt,
+    #[msg("Mint receipt does not belong to the tars, mint number or metadata")]
+    InvalidMintReceipt,
+    #[msg("Holder discount proofs must fill exactly one slot per holder discount, in order")]
+    HolderDiscountProofMismatch,
+    #[msg("Wallet is already whitelisted for another tier")]
+    WhitelistTierMismatch,
 }
-
@@ -6,7 +6,10 @@ pub mod utils;
 pub mod wallet_whitelist;
 pub mod whitelist_config;
 pub mod whitelist_config_instructions;
+pub mod whitelist_delegation;
 pub mod whitelist_instructions;
+pub mod whitelist_raffle;
+pub mod whitelist_raffle_instructions;
 
 
 use anchor_lang::prelude::*;
@@ -14,9 +17,11 @@ pub use errors::TarsError;
 pub use processor::*;
 pub use state::*;
 pub use utils::*;
+pub use wallet_whitelist::*;
 pub use whitelist_config::*;
 pub use whitelist_config_instructions::*;
 pub use whitelist_instructions::*;
+pub use whitelist_raffle_instructions::*;
 
 declare_id!("CASE88YSoMZS8hFter4pzC9jC5g7RkPsjsaxH8uYzgGi");
 
@@ -43,6 +48,14 @@ pub mod tars {
         handle_update_authority(ctx, new_authority)
     }
 
+    pub fn migrate_tars(ctx: Context<MigrateTars>) -> Result<()> {
+        handle_migrate_tars(ctx)
+    }
+
+    pub fn resize_tars(ctx: Context<ResizeTars>, items_available: u64) -> Result<()> {
+        handle_resize_tars(ctx, items_available)
+    }
+
     pub fn add_config_lines(
         ctx: Context<AddConfigLines>,
         index: u32,
@@ -51,6 +64,71 @@ pub mod tars {
         handle_add_config_lines(ctx, index, config_lines)
     }
 
+    pub fn remove_config_lines(
+        ctx: Context<RemoveConfigLines>,
+        index: u32,
+        count: u32,
+    ) -> Result<()> {
+        handle_remove_config_lines(ctx, index, count)
+    }
+
+    pub fn replace_config_lines(
+        ctx: Context<ReplaceConfigLines>,
+        index: u32,
+        config_lines: Vec<ConfigLine>,
+    ) -> Result<()> {
+        handle_replace_config_lines(ctx, index, config_lines)
+    }
+
+    pub fn create_config_page(ctx: Context<CreateConfigPage>, page_index: u32) -> Result<()> {
+        handle_create_config_page(ctx, page_index)
+    }
+
+    pub fn lock_config(ctx: Context<LockConfig>) -> Result<()> {
+        handle_lock_config(ctx)
+    }
+
+    pub fn create_config_manifest(ctx: Context<CreateConfigManifest>) -> Result<()> {
+        handle_create_config_manifest(ctx)
+    }
+
+    pub fn hash_config_lines(ctx: Context<HashConfigLines>, count: u32) -> Result<()> {
+        handle_hash_config_lines(ctx, count)
+    }
+
+    pub fn commit_reveal(ctx: Context<CommitReveal>, root: [u8; 32]) -> Result<()> {
+        handle_commit_reveal(ctx, root)
+    }
+
+    pub fn reveal_nft(
+        ctx: Context<RevealNft>,
+        creator_bump: u8,
+        mint_number: u64,
+        config_line: ConfigLine,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        handle_reveal_nft(ctx, creator_bump, mint_number, config_line, proof)
+    }
+
+    pub fn commit_reveal_stages(
+        ctx: Context<CommitRevealStages>,
+        stages: Vec<RevealStage>,
+    ) -> Result<()> {
+        handle_commit_reveal_stages(ctx, stages)
+    }
+
+    pub fn close_reveal_schedule(ctx: Context<CloseRevealSchedule>) -> Result<()> {
+        handle_close_reveal_schedule(ctx)
+    }
+
+    pub fn reveal_nfts<'info>(
+        ctx: Context<'_, '_, '_, 'info, RevealNfts<'info>>,
+        creator_bump: u8,
+        reveals: Vec<NftReveal>,
+    ) -> Result<()> {
+        handle_reveal_nfts(ctx, creator_bump, reveals)
+    }
+
     pub fn set_collection(ctx: Context<SetCollection>) -> Result<()> {
         handle_set_collection(ctx)
     }
@@ -59,6 +137,28 @@ pub mod tars {
         handle_remove_collection(ctx)
     }
 
+    pub fn create_blocklist(ctx: Context<CreateBlocklist>, max_wallets: u32) -> Result<()> {
+        handle_create_blocklist(ctx, max_wallets)
+    }
+
+    pub fn add_blocked_wallets(
+        ctx: Context<AddBlockedWallets>,
+        wallets: Vec<Pubkey>,
+    ) -> Result<()> {
+        handle_add_blocked_wallets(ctx, wallets)
+    }
+
+    pub fn remove_blocked_wallets(
+        ctx: Context<RemoveBlockedWallets>,
+        wallets: Vec<Pubkey>,
+    ) -> Result<()> {
+        handle_remove_blocked_wallets(ctx, wallets)
+    }
+
+    pub fn delete_blocklist(ctx: Context<DeleteBlocklist>) -> Result<()> {
+        handle_delete_blocklist(ctx)
+    }
+
     pub fn mint_nft<'info>(
         ctx: Context<'_, '_, '_, 'info, MintNFT<'info>>,
         creator_bump: u8,
@@ -92,71 +192,129 @@ pub mod tars {
         handle_delete_whitelist_account(ctx)
     }
 
-    pub fn create_whitelist_config(
-        ctx: Context<CreateWhitelistConfig>,
-        wl_start_time_4_wl_spots: u64,
-        wl_start_time_4_wl_mp: u64,
-        wl_start_time_4_wl_st: u64,
-        wl_start_time_3_wl_spots: u64,
-        wl_start_time_3_wl_mp: u64,
-        wl_start_time_3_wl_st: u64,
-        wl_start_time_2_wl_spots: u64,
-        wl_start_time_2_wl_mp: u64,
-        wl_start_time_2_wl_st: u64,
-        wl_start_time_1_wl_spots: u64,
-        wl_start_time_1_wl_mp: u64,
-        wl_start_time_1_wl_st: u64,
-    ) -> Result<()> {
-        handler_create_whitelist_config(
+    pub fn create_whitelist_accounts<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateWhitelistAccounts<'info>>,
+        entries: Vec<WhitelistEntry>,
+    ) -> Result<()> {
+        handle_create_whitelist_accounts(ctx, entries)
+    }
+
+    pub fn delete_whitelist_accounts<'info>(
+        ctx: Context<'_, '_, '_, 'info, DeleteWhitelistAccounts<'info>>,
+        whitelisted_addresses: Vec<Pubkey>,
+    ) -> Result<()> {
+        handle_delete_whitelist_accounts(ctx, whitelisted_addresses)
+    }
+
+    pub fn update_whitelist_account(
+        ctx: Context<UpdateWhitelistAccount>,
+        discounted_mint_price: Option<u64>,
+        whitelist_mint_start_time: Option<u64>,
+    ) -> Result<()> {
+        handle_update_whitelist_account(ctx, discounted_mint_price, whitelist_mint_start_time)
+    }
+
+    pub fn migrate_wallet_whitelist(ctx: Context<MigrateWalletWhitelist>) -> Result<()> {
+        handle_migrate_wallet_whitelist(ctx)
+    }
+
+    pub fn close_expired_whitelist_account(
+        ctx: Context<CloseExpiredWhitelistAccount>,
+    ) -> Result<()> {
+        handle_close_expired_whitelist_account(ctx)
+    }
+
+    pub fn delegate_whitelist_spots(
+        ctx: Context<DelegateWhitelistSpots>,
+        number_of_delegated_spots: u64,
+    ) -> Result<()> {
+        handle_delegate_whitelist_spots(ctx, number_of_delegated_spots)
+    }
+
+    pub fn revoke_whitelist_delegation(ctx: Context<RevokeWhitelistDelegation>) -> Result<()> {
+        handle_revoke_whitelist_delegation(ctx)
+    }
+
+    pub fn create_whitelist_token_mint(ctx: Context<CreateWhitelistTokenMint>) -> Result<()> {
+        handle_create_whitelist_token_mint(ctx)
+    }
+
+    pub fn tokenize_whitelist_spots(
+        ctx: Context<TokenizeWhitelistSpots>,
+        amount: u64,
+    ) -> Result<()> {
+        handle_tokenize_whitelist_spots(ctx, amount)
+    }
+
+    pub fn redeem_whitelist_tokens(ctx: Context<RedeemWhitelistTokens>, amount: u64) -> Result<()> {
+        handle_redeem_whitelist_tokens(ctx, amount)
+    }
+
+    pub fn create_whitelist_raffle(
+        ctx: Context<CreateWhitelistRaffle>,
+        whitelist_type: WLType,
+        registration_start_time: u64,
+        registration_end_time: u64,
+        settlement_end_time: u64,
+        deposit: u64,
+        number_of_winners: u32,
+    ) -> Result<()> {
+        handle_create_whitelist_raffle(
             ctx,
-            wl_start_time_4_wl_spots,
-            wl_start_time_4_wl_mp,
-            wl_start_time_4_wl_st,
-            wl_start_time_3_wl_spots,
-            wl_start_time_3_wl_mp,
-            wl_start_time_3_wl_st,
-            wl_start_time_2_wl_spots,
-            wl_start_time_2_wl_mp,
-            wl_start_time_2_wl_st,
-            wl_start_time_1_wl_spots,
-            wl_start_time_1_wl_mp,
-            wl_start_time_1_wl_st,
+            whitelist_type,
+            registration_start_time,
+            registration_end_time,
+            settlement_end_time,
+            deposit,
+            number_of_winners,
         )
     }
 
+    pub fn register_for_whitelist_raffle(ctx: Context<RegisterForWhitelistRaffle>) -> Result<()> {
+        handle_register_for_whitelist_raffle(ctx)
+    }
+
+    pub fn close_whitelist_raffle_registration(
+        ctx: Context<CloseWhitelistRaffleRegistration>,
+    ) -> Result<()> {
+        handle_close_whitelist_raffle_registration(ctx)
+    }
+
+    pub fn draw_whitelist_raffle(ctx: Context<DrawWhitelistRaffle>) -> Result<()> {
+        handle_draw_whitelist_raffle(ctx)
+    }
+
+    pub fn settle_raffle_ticket(ctx: Context<SettleRaffleTicket>) -> Result<()> {
+        handle_settle_raffle_ticket(ctx)
+    }
+
+    pub fn reclaim_raffle_ticket(ctx: Context<ReclaimRaffleTicket>) -> Result<()> {
+        handle_reclaim_raffle_ticket(ctx)
+    }
+
+    pub fn delete_whitelist_raffle(ctx: Context<DeleteWhitelistRaffle>) -> Result<()> {
+        handle_delete_whitelist_raffle(ctx)
+    }
+
+    pub fn create_whitelist_config(
+        ctx: Context<CreateWhitelistConfig>,
+        whitelist_schedule: WhitelistSchedule,
+    ) -> Result<()> {
+        handler_create_whitelist_config(ctx, whitelist_schedule)
+    }
+
     pub fn delete_whitelist_config(ctx: Context<DeleteWhitelistConfig>) -> Result<()> {
         handle_delete_whitelist_config(ctx)
     }
 
+    pub fn migrate_whitelist_config(ctx: Context<MigrateWhitelistConfig>) -> Result<()> {
+        handle_migrate_whitelist_config(ctx)
+    }
+
     pub fn update_whitelist_config(
         ctx: Context<UpdateWhitelistConfig>,
-        wl_start_time_4_wl_spots: u64,
-        wl_start_time_4_wl_mp: u64,
-        wl_start_time_4_wl_st: u64,
-        wl_start_time_3_wl_spots: u64,
-        wl_start_time_3_wl_mp: u64,
-        wl_start_time_3_wl_st: u64,
-        wl_start_time_2_wl_spots: u64,
-        wl_start_time_2_wl_mp: u64,
-        wl_start_time_2_wl_st: u64,
-        wl_start_time_1_wl_spots: u64,
-        wl_start_time_1_wl_mp: u64,
-        wl_start_time_1_wl_st: u64,
-    ) -> Result<()> {
-        handler_update_whitelist_config(
-            ctx,
-            wl_start_time_4_wl_spots,
-            wl_start_time_4_wl_mp,
-            wl_start_time_4_wl_st,
-            wl_start_time_3_wl_spots,
-            wl_start_time_3_wl_mp,
-            wl_start_time_3_wl_st,
-            wl_start_time_2_wl_spots,
-            wl_start_time_2_wl_mp,
-            wl_start_time_2_wl_st,
-            wl_start_time_1_wl_spots,
-            wl_start_time_1_wl_mp,
-            wl_start_time_1_wl_st,
-        )
+        whitelist_schedule: WhitelistSchedule,
+    ) -> Result<()> {
+        handler_update_whitelist_config(ctx, whitelist_schedule)
     }
 }
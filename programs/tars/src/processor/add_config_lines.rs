@@ -2,19 +2,22 @@ use std::cell::RefMut;
 
 use anchor_lang::prelude::*;
 use arrayref::array_ref;
-use mpl_token_metadata::state::{MAX_NAME_LENGTH, MAX_URI_LENGTH};
 
 use crate::{
-    constants::{CONFIG_ARRAY_START, CONFIG_LINE_SIZE},
-    TarsError, Tars, ConfigLine,
+    assert_config_page, config_line_size, config_page_lines,
+    constants::{CONFIG_ARRAY_START, TARS_VERSION},
+    is_config_locked, is_page_line_set, serialize_config_line, set_page_line, split_config_page,
+    ConfigLine, Tars, TarsError,
 };
 
 /// Add multiple config lines to the tars.
 #[derive(Accounts)]
 pub struct AddConfigLines<'info> {
-    #[account(mut, has_one = authority)]
+    #[account(mut, has_one = authority, constraint = tars.version == TARS_VERSION @ TarsError::TarsVersionMismatch)]
     tars: Account<'info, Tars>,
     authority: Signer<'info>,
+    // > Only needed if tars uses config pages, repeated for every page the lines span
+    // config_page
 }
 
 pub fn handle_add_config_lines(
@@ -23,10 +26,6 @@ pub fn handle_add_config_lines(
     config_lines: Vec<ConfigLine>,
 ) -> Result<()> {
     let tars = &mut ctx.accounts.tars;
-    let account = tars.to_account_info();
-    let current_count = get_config_count(&account.data.borrow_mut())?;
-    let mut data = account.data.borrow_mut();
-    let mut fixed_config_lines = Vec::with_capacity(config_lines.len());
     // No risk overflow because you literally cant store this many in an account
     // going beyond u32 only happens with the hidden store Tars, which dont use this.
     if index > (tars.data.items_available as u32) - 1 {
@@ -35,33 +34,63 @@ pub fn handle_add_config_lines(
     if tars.data.hidden_settings.is_some() {
         return err!(TarsError::HiddenSettingsConfigsDoNotHaveConfigLines);
     }
+    if index as usize + config_lines.len() > tars.data.items_available as usize {
+        return err!(TarsError::IndexGreaterThanLength);
+    }
+    // Once locked, lines can only be appended: loaded lines are never written again.
+    let locked = is_config_locked(tars);
+    let line_size = config_line_size(&tars.data);
+    let mut serialized = Vec::with_capacity(config_lines.len() * line_size);
     for line in &config_lines {
-        let array_of_zeroes = vec![0u8; MAX_NAME_LENGTH - line.name.len()];
-        let name = line.name.clone() + std::str::from_utf8(&array_of_zeroes).unwrap();
-
-        let array_of_zeroes = vec![0u8; MAX_URI_LENGTH - line.uri.len()];
-        let uri = line.uri.clone() + std::str::from_utf8(&array_of_zeroes).unwrap();
-        fixed_config_lines.push(ConfigLine { name, uri })
+        serialized.append(&mut serialize_config_line(
+            line,
+            &tars.data.config_line_template,
+        )?);
     }
 
-    let as_vec = fixed_config_lines.try_to_vec()?;
-    // remove unneeded u32 because we're just gonna edit the u32 at the front
-    let serialized: &[u8] = &as_vec.as_slice()[4..];
-
-    let position = CONFIG_ARRAY_START + 4 + (index as usize) * CONFIG_LINE_SIZE;
-
-    let array_slice: &mut [u8] =
-        &mut data[position..position + fixed_config_lines.len() * CONFIG_LINE_SIZE];
+    if let Some(lines_per_page) = config_page_lines(&tars.data) {
+        let newly_loaded = add_config_page_lines(
+            &tars.key(),
+            ctx.remaining_accounts,
+            lines_per_page,
+            index as usize,
+            &serialized,
+            line_size,
+            locked,
+        )?;
+        let account = tars.to_account_info();
+        let mut data = account.data.borrow_mut();
+        let count = get_config_count(&data)? + newly_loaded;
+        data[CONFIG_ARRAY_START..CONFIG_ARRAY_START + 4]
+            .copy_from_slice(&(count as u32).to_le_bytes());
+        return Ok(());
+    }
 
-    array_slice.copy_from_slice(serialized);
+    let account = tars.to_account_info();
+    let current_count = get_config_count(&account.data.borrow_mut())?;
+    let mut data = account.data.borrow_mut();
 
     let bit_mask_vec_start = CONFIG_ARRAY_START
         + 4
-        + (tars.data.items_available as usize) * CONFIG_LINE_SIZE
+        + (tars.data.items_available as usize) * line_size
         + 4;
 
+    if locked {
+        for position in index as usize..index as usize + config_lines.len() {
+            if data[bit_mask_vec_start + position / 8] & (0x80 >> (position % 8)) != 0 {
+                return err!(TarsError::ConfigLocked);
+            }
+        }
+    }
+
+    let position = CONFIG_ARRAY_START + 4 + (index as usize) * line_size;
+
+    let array_slice: &mut [u8] = &mut data[position..position + serialized.len()];
+
+    array_slice.copy_from_slice(&serialized);
+
     let mut new_count = current_count;
-    for i in 0..fixed_config_lines.len() {
+    for i in 0..config_lines.len() {
         let position = (index as usize)
             .checked_add(i)
             .ok_or(TarsError::NumericalOverflowError)?;
@@ -102,6 +131,335 @@ pub fn handle_add_config_lines(
     Ok(())
 }
 
+// Lines of a tars using config pages are written into the pages passed as
+// remaining accounts, one for every page the lines span, in order. Returns the
+// number of lines that were not loaded before.
+fn add_config_page_lines(
+    tars: &Pubkey,
+    config_pages: &[AccountInfo],
+    lines_per_page: usize,
+    index: usize,
+    serialized: &[u8],
+    line_size: usize,
+    locked: bool,
+) -> Result<usize> {
+    let mut lines = serialized.chunks(line_size);
+    let mut newly_loaded = 0;
+    let mut position = index;
+    for config_page_info in config_pages {
+        if lines.len() == 0 {
+            break;
+        }
+        let page_index = position / lines_per_page;
+        assert_config_page(config_page_info, tars, page_index)?;
+
+        let mut data = config_page_info.data.borrow_mut();
+        let (loaded, _, page_lines) = split_config_page(&mut data, lines_per_page);
+        while position / lines_per_page == page_index {
+            let line = match lines.next() {
+                Some(line) => line,
+                None => break,
+            };
+            let slot = position % lines_per_page;
+            if locked && is_page_line_set(loaded, slot) {
+                return err!(TarsError::ConfigLocked);
+            }
+            let start = slot * line_size;
+            page_lines[start..start + line_size].copy_from_slice(line);
+            if !is_page_line_set(loaded, slot) {
+                newly_loaded += 1;
+            }
+            set_page_line(loaded, slot);
+            position += 1;
+        }
+    }
+
+    if lines.len() > 0 {
+        return err!(TarsError::MissingConfigPage);
+    }
+    Ok(newly_loaded)
+}
+
 pub fn get_config_count(data: &RefMut<&mut [u8]>) -> Result<usize> {
     return Ok(u32::from_le_bytes(*array_ref![data, CONFIG_ARRAY_START, 4]) as usize);
 }
+
+// Offsets of the bitmask of loaded lines and of the bitmap of redeemed lines,
+// which both follow the config lines and take `items / 8 + 1` bytes.
+pub fn bit_mask_start(items: usize, line_size: usize) -> usize {
+    CONFIG_ARRAY_START + 4 + items * line_size + 4
+}
+
+pub fn redeemed_start(items: usize, line_size: usize) -> usize {
+    bit_mask_start(items, line_size) + items / 8 + 4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config_page_space, constants::CONFIG_PAGE_MASKS_START, remove_config_page_lines, ConfigPage,
+    };
+
+    const LINES_PER_PAGE: usize = 32;
+    const LINE_SIZE: usize = 2;
+    // After the two bitmasks of 4 bytes
+    const LINES_START: usize = CONFIG_PAGE_MASKS_START + 8;
+
+    fn config_page_data(tars: &Pubkey, index: u32) -> Vec<u8> {
+        let mut data = vec![0u8; config_page_space(LINES_PER_PAGE, LINE_SIZE)];
+        let config_page = ConfigPage { tars: *tars, index };
+        config_page.try_serialize(&mut data.as_mut_slice()).unwrap();
+        data
+    }
+
+    // Pages of 32 lines keep the loaded lines in a little-endian u32
+    fn loaded(config_page_info: &AccountInfo) -> u32 {
+        let data = config_page_info.data.borrow();
+        u32::from_le_bytes(*array_ref![data, CONFIG_PAGE_MASKS_START, 4])
+    }
+
+    // Lines 30 to 35 of the tars, spanning the end of page 0 and page 1.
+    fn lines() -> Vec<u8> {
+        (30..36u8)
+            .flat_map(|position| [position; LINE_SIZE])
+            .collect()
+    }
+
+    #[test]
+    fn tracks_loaded_lines_in_config_pages() {
+        let tars = Pubkey::new_unique();
+        let program_id = crate::id();
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let (mut lamports_0, mut lamports_1) = (1, 1);
+        let mut data_0 = config_page_data(&tars, 0);
+        let mut data_1 = config_page_data(&tars, 1);
+        let config_pages = [
+            AccountInfo::new(
+                &keys[0],
+                false,
+                true,
+                &mut lamports_0,
+                &mut data_0,
+                &program_id,
+                false,
+                0,
+            ),
+            AccountInfo::new(
+                &keys[1],
+                false,
+                true,
+                &mut lamports_1,
+                &mut data_1,
+                &program_id,
+                false,
+                0,
+            ),
+        ];
+
+        let added = add_config_page_lines(
+            &tars,
+            &config_pages,
+            LINES_PER_PAGE,
+            30,
+            &lines(),
+            LINE_SIZE,
+            false,
+        );
+        assert_eq!(added.unwrap(), 6);
+        assert_eq!(loaded(&config_pages[0]), 0b11 << 30);
+        assert_eq!(loaded(&config_pages[1]), 0b1111);
+        let line_33 = LINES_START + LINE_SIZE;
+        assert_eq!(
+            &config_pages[1].data.borrow()[line_33..line_33 + LINE_SIZE],
+            &[33, 33]
+        );
+
+        // lines loaded again aren't counted twice
+        let added = add_config_page_lines(
+            &tars,
+            &config_pages,
+            LINES_PER_PAGE,
+            31,
+            &lines()[..4],
+            LINE_SIZE,
+            false,
+        );
+        assert_eq!(added.unwrap(), 0);
+        // and can't be overwritten once locked, unlike new ones
+        assert!(add_config_page_lines(
+            &tars,
+            &config_pages[1..],
+            LINES_PER_PAGE,
+            35,
+            &lines()[..2],
+            LINE_SIZE,
+            true
+        )
+        .is_err());
+        let added = add_config_page_lines(
+            &tars,
+            &config_pages[1..],
+            LINES_PER_PAGE,
+            36,
+            &lines()[..2],
+            LINE_SIZE,
+            true,
+        );
+        assert_eq!(added.unwrap(), 1);
+        assert_eq!(loaded(&config_pages[1]), 0b11111);
+
+        // pages must follow the lines in order
+        let reversed = [config_pages[1].clone(), config_pages[0].clone()];
+        assert!(add_config_page_lines(
+            &tars,
+            &reversed,
+            LINES_PER_PAGE,
+            30,
+            &lines(),
+            LINE_SIZE,
+            false
+        )
+        .is_err());
+        assert!(add_config_page_lines(
+            &tars,
+            &config_pages[..1],
+            LINES_PER_PAGE,
+            30,
+            &lines(),
+            LINE_SIZE,
+            false
+        )
+        .is_err());
+
+        let removed =
+            remove_config_page_lines(&tars, &config_pages, LINES_PER_PAGE, 31, 34, LINE_SIZE);
+        assert_eq!(removed.unwrap(), 3);
+        assert_eq!(loaded(&config_pages[0]), 1 << 30);
+        assert_eq!(loaded(&config_pages[1]), 0b11100);
+        assert!(
+            config_pages[1].data.borrow()[LINES_START..line_33 + LINE_SIZE]
+                .iter()
+                .all(|byte| *byte == 0)
+        );
+        // lines that weren't loaded aren't counted
+        let removed =
+            remove_config_page_lines(&tars, &config_pages, LINES_PER_PAGE, 30, 34, LINE_SIZE);
+        assert_eq!(removed.unwrap(), 1);
+    }
+
+    #[test]
+    fn tracks_loaded_lines_in_large_config_pages() {
+        let tars = Pubkey::new_unique();
+        let program_id = crate::id();
+        let key = Pubkey::new_unique();
+        let mut lamports = 1;
+        let mut data = vec![0u8; config_page_space(100, LINE_SIZE)];
+        ConfigPage { tars, index: 1 }
+            .try_serialize(&mut data.as_mut_slice())
+            .unwrap();
+        let config_page = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &program_id,
+            false,
+            0,
+        );
+
+        // lines 190 to 195 are the last ones of page 1
+        let added = add_config_page_lines(
+            &tars,
+            &[config_page.clone()],
+            100,
+            190,
+            &lines(),
+            LINE_SIZE,
+            false,
+        );
+        assert_eq!(added.unwrap(), 6);
+        let data = config_page.data.borrow();
+        let masks = &data[CONFIG_PAGE_MASKS_START..CONFIG_PAGE_MASKS_START + 26];
+        assert_eq!(masks[11], 0b1111_1100);
+        assert!(masks[..11]
+            .iter()
+            .chain(&masks[12..])
+            .all(|byte| *byte == 0));
+        // the lines follow both bitmasks of 13 bytes
+        let line_195 = CONFIG_PAGE_MASKS_START + 26 + 95 * LINE_SIZE;
+        assert_eq!(&data[line_195..line_195 + LINE_SIZE], &[35, 35]);
+    }
+
+    #[test]
+    fn removes_lines_of_config_pages_never_created() {
+        let tars = Pubkey::new_unique();
+        let program_id = crate::id();
+        let system_program = solana_program::system_program::ID;
+        let (page_key, _) = Pubkey::find_program_address(
+            &[b"config-page".as_ref(), tars.as_ref(), &1u32.to_le_bytes()],
+            &program_id,
+        );
+        let (mut lamports_0, mut lamports_1) = (1, 0);
+        let mut data_0 = config_page_data(&tars, 0);
+        let mut data_1 = vec![];
+        let key_0 = Pubkey::new_unique();
+        let config_pages = [
+            AccountInfo::new(
+                &key_0,
+                false,
+                true,
+                &mut lamports_0,
+                &mut data_0,
+                &program_id,
+                false,
+                0,
+            ),
+            AccountInfo::new(
+                &page_key,
+                false,
+                true,
+                &mut lamports_1,
+                &mut data_1,
+                &system_program,
+                false,
+                0,
+            ),
+        ];
+        let added = add_config_page_lines(
+            &tars,
+            &config_pages[..1],
+            LINES_PER_PAGE,
+            30,
+            &lines()[..4],
+            LINE_SIZE,
+            false,
+        );
+        assert_eq!(added.unwrap(), 2);
+
+        let removed =
+            remove_config_page_lines(&tars, &config_pages, LINES_PER_PAGE, 30, 40, LINE_SIZE);
+        assert_eq!(removed.unwrap(), 2);
+        assert_eq!(loaded(&config_pages[0]), 0);
+
+        // any other empty account is refused
+        let other_key = Pubkey::new_unique();
+        let (mut lamports, mut data) = (0, vec![]);
+        let other = AccountInfo::new(
+            &other_key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &system_program,
+            false,
+            0,
+        );
+        let config_pages = [config_pages[0].clone(), other];
+        assert!(
+            remove_config_page_lines(&tars, &config_pages, LINES_PER_PAGE, 30, 40, LINE_SIZE)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::{Blocklist, Tars, TarsError};
+
+/// Add wallets to the blocklist of the tars.
+#[derive(Accounts)]
+pub struct AddBlockedWallets<'info> {
+    #[account(has_one = authority)]
+    tars: Account<'info, Tars>,
+    authority: Signer<'info>,
+    #[account(mut, seeds = [b"blocklist".as_ref(), tars.key().as_ref()], bump)]
+    blocklist: Account<'info, Blocklist>,
+}
+
+pub fn handle_add_blocked_wallets(
+    ctx: Context<AddBlockedWallets>,
+    wallets: Vec<Pubkey>,
+) -> Result<()> {
+    let blocklist = &mut ctx.accounts.blocklist;
+    for wallet in wallets {
+        // Keep the list sorted so mints can binary search it.
+        if let Err(index) = blocklist.wallets.binary_search(&wallet) {
+            blocklist.wallets.insert(index, wallet);
+        }
+    }
+    if blocklist.wallets.len() > blocklist.max_wallets as usize {
+        return err!(TarsError::BlocklistFull);
+    }
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::BLOCKLIST_FEATURE_INDEX, Blocklist, Tars};
+
+/// Create the blocklist of the tars, holding up to `max_wallets` wallets.
+#[derive(Accounts)]
+#[instruction(max_wallets: u32)]
+pub struct CreateBlocklist<'info> {
+    #[account(mut, has_one = authority)]
+    tars: Account<'info, Tars>,
+    authority: Signer<'info>,
+    #[account(init,
+        payer = payer,
+        space = 8 + Blocklist::MAX_SERIALIZED_SIZE + 32 * max_wallets as usize,
+        seeds = [b"blocklist".as_ref(), tars.key().as_ref()],
+        bump
+    )]
+    blocklist: Account<'info, Blocklist>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+pub fn handle_create_blocklist(ctx: Context<CreateBlocklist>, max_wallets: u32) -> Result<()> {
+    let blocklist = &mut ctx.accounts.blocklist;
+    blocklist.tars = ctx.accounts.tars.key();
+    blocklist.max_wallets = max_wallets;
+    blocklist.wallets = vec![];
+
+    ctx.accounts.tars.set_feature(BLOCKLIST_FEATURE_INDEX);
+    Ok(())
+}
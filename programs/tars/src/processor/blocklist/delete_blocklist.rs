@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::BLOCKLIST_FEATURE_INDEX, Blocklist, Tars};
+
+/// Delete the blocklist of the tars, unblocking every wallet.
+#[derive(Accounts)]
+pub struct DeleteBlocklist<'info> {
+    #[account(mut, has_one = authority)]
+    tars: Account<'info, Tars>,
+    #[account(mut)]
+    authority: Signer<'info>,
+    #[account(mut, seeds = [b"blocklist".as_ref(), tars.key().as_ref()], bump, close = authority)]
+    blocklist: Account<'info, Blocklist>,
+}
+
+pub fn handle_delete_blocklist(ctx: Context<DeleteBlocklist>) -> Result<()> {
+    ctx.accounts.tars.remove_feature(BLOCKLIST_FEATURE_INDEX);
+    Ok(())
+}
@@ -0,0 +1,9 @@
+pub mod add_blocked_wallets;
+pub mod create_blocklist;
+pub mod delete_blocklist;
+pub mod remove_blocked_wallets;
+
+pub use add_blocked_wallets::*;
+pub use create_blocklist::*;
+pub use delete_blocklist::*;
+pub use remove_blocked_wallets::*;
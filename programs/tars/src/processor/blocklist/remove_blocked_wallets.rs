@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::{Blocklist, Tars};
+
+/// Remove wallets from the blocklist of the tars.
+#[derive(Accounts)]
+pub struct RemoveBlockedWallets<'info> {
+    #[account(has_one = authority)]
+    tars: Account<'info, Tars>,
+    authority: Signer<'info>,
+    #[account(mut, seeds = [b"blocklist".as_ref(), tars.key().as_ref()], bump)]
+    blocklist: Account<'info, Blocklist>,
+}
+
+pub fn handle_remove_blocked_wallets(
+    ctx: Context<RemoveBlockedWallets>,
+    wallets: Vec<Pubkey>,
+) -> Result<()> {
+    let blocklist = &mut ctx.accounts.blocklist;
+    for wallet in wallets {
+        if let Ok(index) = blocklist.wallets.binary_search(&wallet) {
+            blocklist.wallets.remove(index);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use solana_program::hash::hash;
+
+use crate::{constants::TARS_VERSION, is_config_locked, ConfigManifest, Tars, TarsError};
+
+/// Create the manifest of a tars whose config lines are locked, either by
+/// `lock_config` or by the first mint. Anyone can create it.
+#[derive(Accounts)]
+pub struct CreateConfigManifest<'info> {
+    #[account(constraint = tars.version == TARS_VERSION @ TarsError::TarsVersionMismatch)]
+    tars: Account<'info, Tars>,
+    #[account(init,
+        payer = payer,
+        space = 8 + ConfigManifest::MAX_SERIALIZED_SIZE,
+        seeds = [b"config-manifest".as_ref(), tars.key().as_ref()],
+        bump
+    )]
+    config_manifest: Account<'info, ConfigManifest>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+pub fn handle_create_config_manifest(ctx: Context<CreateConfigManifest>) -> Result<()> {
+    let tars = &ctx.accounts.tars;
+    if !is_config_locked(tars) {
+        return err!(TarsError::ConfigNotLocked);
+    }
+
+    let config_manifest = &mut ctx.accounts.config_manifest;
+    config_manifest.tars = tars.key();
+    config_manifest.lines_hashed = 0;
+    config_manifest.hash = hash(&tars.data.config_line_template.try_to_vec()?).to_bytes();
+    Ok(())
+}
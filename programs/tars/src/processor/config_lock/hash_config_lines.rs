@@ -0,0 +1,149 @@
+use std::ops::Range;
+
+use anchor_lang::prelude::*;
+use solana_program::hash::hashv;
+
+use crate::{
+    assert_config_page, bit_mask_start, config_line_size, config_page_lines,
+    config_page_lines_start,
+    constants::{CONFIG_ARRAY_START, CONFIG_PAGE_MASKS_START, TARS_VERSION},
+    is_config_locked, is_page_line_set, ConfigManifest, Tars, TarsError,
+};
+
+/// Add the next `count` config lines of a locked tars to its manifest hash.
+/// Lines added after a locked tars grew are hashed once they are loaded.
+#[derive(Accounts)]
+pub struct HashConfigLines<'info> {
+    #[account(constraint = tars.version == TARS_VERSION @ TarsError::TarsVersionMismatch)]
+    tars: Account<'info, Tars>,
+    #[account(mut, seeds = [b"config-manifest".as_ref(), tars.key().as_ref()], bump)]
+    config_manifest: Account<'info, ConfigManifest>,
+    // > Only needed if tars uses config pages, repeated for every page the lines span
+    // config_page
+}
+
+pub fn handle_hash_config_lines(ctx: Context<HashConfigLines>, count: u32) -> Result<()> {
+    let tars = &ctx.accounts.tars;
+    let config_manifest = &mut ctx.accounts.config_manifest;
+    if !is_config_locked(tars) {
+        return err!(TarsError::ConfigNotLocked);
+    }
+
+    let line_size = config_line_size(&tars.data);
+    let start = config_manifest.lines_hashed as usize;
+    let end = start
+        .saturating_add(count as usize)
+        .min(tars.data.items_available as usize);
+    let mut hash = config_manifest.hash;
+
+    if let Some(lines_per_page) = config_page_lines(&tars.data) {
+        let mut config_pages = ctx.remaining_accounts.iter();
+        let mut position = start;
+        while position < end {
+            let page_index = position / lines_per_page;
+            let config_page_info = config_pages.next().ok_or(TarsError::MissingConfigPage)?;
+            assert_config_page(config_page_info, &tars.key(), page_index)?;
+
+            let data = config_page_info.data.borrow();
+            let loaded = &data[CONFIG_PAGE_MASKS_START..];
+            let lines_start = config_page_lines_start(lines_per_page);
+            while position < end && position / lines_per_page == page_index {
+                let slot = position % lines_per_page;
+                if !is_page_line_set(loaded, slot) {
+                    return err!(TarsError::TarsNotFullyLoaded);
+                }
+                let line_start = lines_start + slot * line_size;
+                hash = hashv(&[&hash, &data[line_start..line_start + line_size]]).to_bytes();
+                position += 1;
+            }
+        }
+    } else {
+        let tars_info = tars.to_account_info();
+        let data = tars_info.data.borrow();
+        hash = hash_account_config_lines(
+            &data,
+            tars.data.items_available as usize,
+            line_size,
+            start..end,
+            hash,
+        )?;
+    }
+
+    config_manifest.hash = hash;
+    config_manifest.lines_hashed = end as u64;
+    Ok(())
+}
+
+// Chains the config lines at `positions` of a tars holding its config lines
+// onto `hash`, all of them must be loaded.
+fn hash_account_config_lines(
+    data: &[u8],
+    items_available: usize,
+    line_size: usize,
+    positions: Range<usize>,
+    mut hash: [u8; 32],
+) -> Result<[u8; 32]> {
+    let bit_mask_start = bit_mask_start(items_available, line_size);
+    for position in positions {
+        if data[bit_mask_start + position / 8] & (0x80 >> (position % 8)) == 0 {
+            return err!(TarsError::TarsNotFullyLoaded);
+        }
+        let line_start = CONFIG_ARRAY_START + 4 + position * line_size;
+        hash = hashv(&[&hash, &data[line_start..line_start + line_size]]).to_bytes();
+    }
+    Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redeemed_start;
+    use solana_program::hash::hash;
+
+    const ITEMS: usize = 6;
+    const LINE_SIZE: usize = 3;
+
+    fn config_data(loaded: usize) -> Vec<u8> {
+        let mut data = vec![0u8; redeemed_start(ITEMS, LINE_SIZE) + ITEMS / 8 + 1];
+        let bit_mask_start = bit_mask_start(ITEMS, LINE_SIZE);
+        for position in 0..loaded {
+            let start = CONFIG_ARRAY_START + 4 + position * LINE_SIZE;
+            data[start..start + LINE_SIZE].fill(position as u8 + 1);
+            data[bit_mask_start + position / 8] |= 0x80 >> (position % 8);
+        }
+        data
+    }
+
+    #[test]
+    fn chains_config_lines_in_batches() {
+        let data = config_data(ITEMS);
+        let seed = hash(&None::<crate::ConfigLineTemplate>.try_to_vec().unwrap()).to_bytes();
+
+        let mut expected = seed;
+        for position in 0..ITEMS {
+            let line = [position as u8 + 1; LINE_SIZE];
+            expected = hashv(&[&expected, &line]).to_bytes();
+        }
+
+        let whole = hash_account_config_lines(&data, ITEMS, LINE_SIZE, 0..ITEMS, seed).unwrap();
+        assert_eq!(whole, expected);
+        let first = hash_account_config_lines(&data, ITEMS, LINE_SIZE, 0..2, seed).unwrap();
+        let batched = hash_account_config_lines(&data, ITEMS, LINE_SIZE, 2..ITEMS, first).unwrap();
+        assert_eq!(batched, expected);
+
+        // the order of the lines is part of the hash
+        let reversed = (0..ITEMS).rev().fold(seed, |hash, position| {
+            hash_account_config_lines(&data, ITEMS, LINE_SIZE, position..position + 1, hash)
+                .unwrap()
+        });
+        assert_ne!(reversed, expected);
+    }
+
+    #[test]
+    fn refuses_unloaded_config_lines() {
+        let data = config_data(4);
+        let seed = [0u8; 32];
+        assert!(hash_account_config_lines(&data, ITEMS, LINE_SIZE, 0..4, seed).is_ok());
+        assert!(hash_account_config_lines(&data, ITEMS, LINE_SIZE, 2..5, seed).is_err());
+    }
+}
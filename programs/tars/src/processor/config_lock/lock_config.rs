@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG_LOCKED_FEATURE_INDEX, TARS_VERSION},
+    get_config_count, Tars, TarsError,
+};
+
+/// Lock the config lines of the tars before the first mint does.
+#[derive(Accounts)]
+pub struct LockConfig<'info> {
+    #[account(mut, has_one = authority, constraint = tars.version == TARS_VERSION @ TarsError::TarsVersionMismatch)]
+    tars: Account<'info, Tars>,
+    authority: Signer<'info>,
+}
+
+pub fn handle_lock_config(ctx: Context<LockConfig>) -> Result<()> {
+    let tars = &mut ctx.accounts.tars;
+    if tars.data.hidden_settings.is_some() {
+        return err!(TarsError::HiddenSettingsConfigsDoNotHaveConfigLines);
+    }
+    // Tars using config pages count their loaded lines the same way
+    let tars_info = tars.to_account_info();
+    if get_config_count(&tars_info.data.borrow_mut())? < tars.data.items_available as usize {
+        return err!(TarsError::TarsNotFullyLoaded);
+    }
+
+    tars.set_feature(CONFIG_LOCKED_FEATURE_INDEX);
+    Ok(())
+}
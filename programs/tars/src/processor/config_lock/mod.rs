@@ -0,0 +1,7 @@
+pub mod create_config_manifest;
+pub mod hash_config_lines;
+pub mod lock_config;
+
+pub use create_config_manifest::*;
+pub use hash_config_lines::*;
+pub use lock_config::*;
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    config_line_size, config_page_lines, config_page_space, constants::TARS_VERSION, ConfigPage,
+    Tars, TarsError,
+};
+
+/// Create the config page of the tars holding the lines from
+/// `page_index * lines_per_page`.
+#[derive(Accounts)]
+#[instruction(page_index: u32)]
+pub struct CreateConfigPage<'info> {
+    #[account(has_one = authority, constraint = tars.version == TARS_VERSION @ TarsError::TarsVersionMismatch)]
+    tars: Account<'info, Tars>,
+    authority: Signer<'info>,
+    #[account(init,
+        payer = payer,
+        space = config_page_space(
+            config_page_lines(&tars.data).unwrap_or_default(),
+            config_line_size(&tars.data),
+        ),
+        seeds = [b"config-page".as_ref(), tars.key().as_ref(), page_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    config_page: Account<'info, ConfigPage>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+pub fn handle_create_config_page(ctx: Context<CreateConfigPage>, page_index: u32) -> Result<()> {
+    let tars = &ctx.accounts.tars;
+    let lines_per_page = match config_page_lines(&tars.data) {
+        Some(lines_per_page) => lines_per_page,
+        None => return err!(TarsError::ConfigPagesNotEnabled),
+    };
+    if page_index as u64 * lines_per_page as u64 >= tars.data.items_available {
+        return err!(TarsError::IndexGreaterThanLength);
+    }
+
+    // Both bitmasks start out empty
+    let config_page = &mut ctx.accounts.config_page;
+    config_page.tars = tars.key();
+    config_page.index = page_index;
+    Ok(())
+}
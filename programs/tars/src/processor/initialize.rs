@@ -3,12 +3,18 @@ use mpl_token_metadata::state::{MAX_CREATOR_LIMIT, MAX_SYMBOL_LENGTH};
 use spl_token::state::Mint;
 
 use crate::{
-    assert_initialized, assert_owned_by, cmp_pubkeys,
-    constants::{CONFIG_ARRAY_START, CONFIG_LINE_SIZE},
-    TarsError, Tars, TarsData,
+    assert_initialized, assert_owned_by, assert_valid_burn_to_mint,
+    assert_valid_config_line_template, assert_valid_config_pages, assert_valid_gatekeeper,
+    assert_valid_holder_discounts, cmp_pubkeys, config_line_size, config_page_count,
+    config_page_lines,
+    constants::{CONFIG_ARRAY_START, TARS_VERSION, USED_CONFIG_PAGES_START},
+    Tars, TarsData, TarsError,
 };
 
 /// Create a new tars.
+///
+/// Setting `config_pages` keeps the config lines in config pages instead of the
+/// tars account.
 #[derive(Accounts)]
 #[instruction(data: TarsData)]
 pub struct InitializeTars<'info> {
@@ -35,11 +41,16 @@ pub fn handle_initialize_tars(
     }
 
     let mut tars = Tars {
+        version: TARS_VERSION,
         data,
         authority: ctx.accounts.authority.key(),
         wallet: ctx.accounts.wallet.key(),
         token_mint: None,
         items_redeemed: 0,
+        last_mint_slot: 0,
+        mints_in_slot: 0,
+        proceeds: 0,
+        features: 0,
     };
 
     if !ctx.remaining_accounts.is_empty() {
@@ -69,6 +80,11 @@ pub fn handle_initialize_tars(
     if tars.data.creators.len() > MAX_CREATOR_LIMIT - 1 {
         return err!(TarsError::TooManyCreators);
     }
+    assert_valid_gatekeeper(&tars.data.gatekeeper)?;
+    assert_valid_burn_to_mint(&tars.data.burn_to_mint)?;
+    assert_valid_holder_discounts(&tars.data.holder_discounts)?;
+    assert_valid_config_line_template(&tars.data.config_line_template)?;
+    assert_valid_config_pages(&tars.data)?;
 
     let mut new_data = Tars::discriminator().try_to_vec().unwrap();
     new_data.append(&mut tars.try_to_vec().unwrap());
@@ -78,12 +94,12 @@ pub fn handle_initialize_tars(
         data[i] = new_data[i];
     }
 
-    // only if we are not using hidden settings we will have space for
-    // the config lines
-    if tars.data.hidden_settings.is_none() {
+    // only if we are not using hidden settings or config pages we will have
+    // space for the config lines
+    if tars.data.hidden_settings.is_none() && tars.data.config_pages.is_none() {
         let vec_start = CONFIG_ARRAY_START
             + 4
-            + (tars.data.items_available as usize) * CONFIG_LINE_SIZE;
+            + (tars.data.items_available as usize) * config_line_size(&tars.data);
         let as_bytes = (tars
             .data
             .items_available
@@ -98,13 +114,18 @@ pub fn handle_initialize_tars(
     Ok(())
 }
 
-fn get_space_for_tars(data: TarsData) -> Result<usize> {
+pub fn get_space_for_tars(data: TarsData) -> Result<usize> {
     let num = if data.hidden_settings.is_some() {
         CONFIG_ARRAY_START
+    } else if let Some(lines_per_page) = config_page_lines(&data) {
+        // The settings are only checked after account constraints call this
+        USED_CONFIG_PAGES_START
+            + config_page_count(data.items_available, lines_per_page.max(1)) / 8
+            + 1
     } else {
         CONFIG_ARRAY_START
             + 4
-            + (data.items_available as usize) * CONFIG_LINE_SIZE
+            + (data.items_available as usize) * config_line_size(&data)
             + 8
             + 2 * ((data
                 .items_available
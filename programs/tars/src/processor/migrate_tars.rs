@@ -0,0 +1,618 @@
+use anchor_lang::{prelude::*, Discriminator};
+use solana_program::{program::invoke, system_instruction};
+
+use crate::{
+    cmp_pubkeys,
+    constants::{
+        BLOCKLIST_FEATURE_INDEX, CONFIG_ARRAY_START, CONFIG_LOCKED_FEATURE_INDEX,
+        LEGACY_CONFIG_ARRAY_START, REVEAL_FEATURE_INDEX, STAGED_REVEAL_FEATURE_INDEX, TARS_VERSION,
+        V1_CONFIG_ARRAY_START, V2_CONFIG_ARRAY_START,
+    },
+    BurnToMintSettings, CollectionGateSettings, ConfigLineTemplate, ConfigPageSettings, Creator,
+    EndSettings, GatekeeperConfig, GatekeeperPolicy, GatekeeperSettings, HiddenSettings,
+    HolderDiscount, RateLimitSettings, Tars, TarsData, TarsError, WhitelistMintSettings,
+};
+
+/// Versions 1 and 2 turned config pages on with this uuid flag, their pages
+/// hold 32 lines and keep the same layout.
+const LEGACY_CONFIG_PAGES_FEATURE_INDEX: usize = 2;
+const LEGACY_CONFIG_PAGE_LINES: u32 = 32;
+
+/// Rewrite a tars account of an older layout version into the current layout.
+#[derive(Accounts)]
+pub struct MigrateTars<'info> {
+    /// CHECK: layout and authority checked in handler
+    #[account(mut, owner = crate::id())]
+    tars: UncheckedAccount<'info>,
+    authority: Signer<'info>,
+    /// CHECK: only checked for existence, backs the blocklist flag of version 1 and 2 accounts
+    #[account(seeds = [b"blocklist".as_ref(), tars.key().as_ref()], bump)]
+    blocklist: UncheckedAccount<'info>,
+    /// CHECK: only checked for existence, backs the staged reveal flag of version 1 and 2 accounts
+    #[account(seeds = [b"reveal-schedule".as_ref(), tars.key().as_ref()], bump)]
+    reveal_schedule: UncheckedAccount<'info>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+/// Which accounts backing the feature flags of a version 1 or 2 tars exist.
+#[derive(Clone, Copy, Default)]
+struct FeatureAccounts {
+    blocklist: bool,
+    reveal_schedule: bool,
+}
+
+/// Tars header as deployed before the layout was versioned.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct BaselineTars {
+    authority: Pubkey,
+    wallet: Pubkey,
+    token_mint: Option<Pubkey>,
+    items_redeemed: u64,
+    data: BaselineTarsData,
+}
+
+/// Tars settings data as deployed before the layout was versioned.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct BaselineTarsData {
+    uuid: String,
+    price: u64,
+    symbol: String,
+    seller_fee_basis_points: u16,
+    max_supply: u64,
+    is_mutable: bool,
+    retain_authority: bool,
+    go_live_date: Option<i64>,
+    end_settings: Option<BaselineEndSettings>,
+    creators: Vec<Creator>,
+    hidden_settings: Option<HiddenSettings>,
+    whitelist_mint_settings: Option<WhitelistMintSettings>,
+    items_available: u64,
+    gatekeeper: Option<GatekeeperConfig>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct BaselineEndSettings {
+    end_setting_type: BaselineEndSettingType,
+    number: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+enum BaselineEndSettingType {
+    Date,
+    Amount,
+}
+
+impl From<BaselineEndSettings> for EndSettings {
+    fn from(end_settings: BaselineEndSettings) -> Self {
+        match end_settings.end_setting_type {
+            BaselineEndSettingType::Date => EndSettings {
+                end_date: Some(end_settings.number as i64),
+                max_redeemed: None,
+                max_proceeds: None,
+            },
+            BaselineEndSettingType::Amount => EndSettings {
+                end_date: None,
+                max_redeemed: Some(end_settings.number),
+                max_proceeds: None,
+            },
+        }
+    }
+}
+
+impl From<BaselineTars> for Tars {
+    fn from(tars: BaselineTars) -> Self {
+        let data = tars.data;
+        Tars {
+            version: TARS_VERSION,
+            authority: tars.authority,
+            wallet: tars.wallet,
+            token_mint: tars.token_mint,
+            items_redeemed: tars.items_redeemed,
+            data: TarsData {
+                uuid: data.uuid,
+                price: data.price,
+                symbol: data.symbol,
+                seller_fee_basis_points: data.seller_fee_basis_points,
+                max_supply: data.max_supply,
+                is_mutable: data.is_mutable,
+                retain_authority: data.retain_authority,
+                go_live_date: data.go_live_date,
+                end_settings: data.end_settings.map(EndSettings::from),
+                creators: data.creators,
+                hidden_settings: data.hidden_settings,
+                whitelist_mint_settings: data.whitelist_mint_settings,
+                items_available: data.items_available,
+                gatekeeper: data.gatekeeper.map(|gatekeeper| GatekeeperSettings {
+                    networks: vec![gatekeeper],
+                    policy: GatekeeperPolicy::Any,
+                }),
+                collection_gate: None,
+                burn_to_mint: None,
+                holder_discounts: vec![],
+                authorized_signer: None,
+                rate_limit: None,
+                config_line_template: None,
+                config_pages: None,
+            },
+            last_mint_slot: 0,
+            mints_in_slot: 0,
+            proceeds: 0,
+            features: 0,
+        }
+    }
+}
+
+/// Tars header as written by layout version 1, after its version byte.
+#[derive(AnchorDeserialize)]
+struct TarsV1 {
+    authority: Pubkey,
+    wallet: Pubkey,
+    token_mint: Option<Pubkey>,
+    items_redeemed: u64,
+    data: TarsDataV1,
+    last_mint_slot: u64,
+    mints_in_slot: u16,
+    proceeds: u64,
+}
+
+/// Tars settings data of layout version 1, from before config line templates.
+#[derive(AnchorDeserialize)]
+struct TarsDataV1 {
+    uuid: String,
+    price: u64,
+    symbol: String,
+    seller_fee_basis_points: u16,
+    max_supply: u64,
+    is_mutable: bool,
+    retain_authority: bool,
+    go_live_date: Option<i64>,
+    end_settings: Option<EndSettings>,
+    creators: Vec<Creator>,
+    hidden_settings: Option<HiddenSettings>,
+    whitelist_mint_settings: Option<WhitelistMintSettings>,
+    items_available: u64,
+    gatekeeper: Option<GatekeeperSettings>,
+    collection_gate: Option<CollectionGateSettings>,
+    burn_to_mint: Option<BurnToMintSettings>,
+    holder_discounts: Vec<HolderDiscount>,
+    authorized_signer: Option<Pubkey>,
+    rate_limit: Option<RateLimitSettings>,
+}
+
+impl From<TarsV1> for Tars {
+    fn from(tars: TarsV1) -> Self {
+        let data = tars.data;
+        Tars {
+            version: TARS_VERSION,
+            authority: tars.authority,
+            wallet: tars.wallet,
+            token_mint: tars.token_mint,
+            items_redeemed: tars.items_redeemed,
+            data: TarsData {
+                config_pages: legacy_config_pages(&data.uuid, &data.hidden_settings),
+                uuid: data.uuid,
+                price: data.price,
+                symbol: data.symbol,
+                seller_fee_basis_points: data.seller_fee_basis_points,
+                max_supply: data.max_supply,
+                is_mutable: data.is_mutable,
+                retain_authority: data.retain_authority,
+                go_live_date: data.go_live_date,
+                end_settings: data.end_settings,
+                creators: data.creators,
+                hidden_settings: data.hidden_settings,
+                whitelist_mint_settings: data.whitelist_mint_settings,
+                items_available: data.items_available,
+                gatekeeper: data.gatekeeper,
+                collection_gate: data.collection_gate,
+                burn_to_mint: data.burn_to_mint,
+                holder_discounts: data.holder_discounts,
+                authorized_signer: data.authorized_signer,
+                rate_limit: data.rate_limit,
+                config_line_template: None,
+            },
+            last_mint_slot: tars.last_mint_slot,
+            mints_in_slot: tars.mints_in_slot,
+            proceeds: tars.proceeds,
+            features: 0,
+        }
+    }
+}
+
+/// Tars header as written by layout version 2, after its version byte.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct TarsV2 {
+    authority: Pubkey,
+    wallet: Pubkey,
+    token_mint: Option<Pubkey>,
+    items_redeemed: u64,
+    data: TarsDataV2,
+    last_mint_slot: u64,
+    mints_in_slot: u16,
+    proceeds: u64,
+}
+
+/// Tars settings data of layout version 2, from before config page settings.
+#[derive(AnchorSerialize, AnchorDeserialize, Default)]
+struct TarsDataV2 {
+    uuid: String,
+    price: u64,
+    symbol: String,
+    seller_fee_basis_points: u16,
+    max_supply: u64,
+    is_mutable: bool,
+    retain_authority: bool,
+    go_live_date: Option<i64>,
+    end_settings: Option<EndSettings>,
+    creators: Vec<Creator>,
+    hidden_settings: Option<HiddenSettings>,
+    whitelist_mint_settings: Option<WhitelistMintSettings>,
+    items_available: u64,
+    gatekeeper: Option<GatekeeperSettings>,
+    collection_gate: Option<CollectionGateSettings>,
+    burn_to_mint: Option<BurnToMintSettings>,
+    holder_discounts: Vec<HolderDiscount>,
+    authorized_signer: Option<Pubkey>,
+    rate_limit: Option<RateLimitSettings>,
+    config_line_template: Option<ConfigLineTemplate>,
+}
+
+impl From<TarsV2> for Tars {
+    fn from(tars: TarsV2) -> Self {
+        let data = tars.data;
+        Tars {
+            version: TARS_VERSION,
+            authority: tars.authority,
+            wallet: tars.wallet,
+            token_mint: tars.token_mint,
+            items_redeemed: tars.items_redeemed,
+            data: TarsData {
+                config_pages: legacy_config_pages(&data.uuid, &data.hidden_settings),
+                uuid: data.uuid,
+                price: data.price,
+                symbol: data.symbol,
+                seller_fee_basis_points: data.seller_fee_basis_points,
+                max_supply: data.max_supply,
+                is_mutable: data.is_mutable,
+                retain_authority: data.retain_authority,
+                go_live_date: data.go_live_date,
+                end_settings: data.end_settings,
+                creators: data.creators,
+                hidden_settings: data.hidden_settings,
+                whitelist_mint_settings: data.whitelist_mint_settings,
+                items_available: data.items_available,
+                gatekeeper: data.gatekeeper,
+                collection_gate: data.collection_gate,
+                burn_to_mint: data.burn_to_mint,
+                holder_discounts: data.holder_discounts,
+                authorized_signer: data.authorized_signer,
+                rate_limit: data.rate_limit,
+                config_line_template: data.config_line_template,
+            },
+            last_mint_slot: tars.last_mint_slot,
+            mints_in_slot: tars.mints_in_slot,
+            proceeds: tars.proceeds,
+            features: 0,
+        }
+    }
+}
+
+/// Config page settings of a version 1 or 2 tars, which laid out its config
+/// lines in pages whenever the uuid flag was set.
+fn legacy_config_pages(
+    uuid: &str,
+    hidden_settings: &Option<HiddenSettings>,
+) -> Option<ConfigPageSettings> {
+    (hidden_settings.is_none()
+        && uuid.as_bytes().get(LEGACY_CONFIG_PAGES_FEATURE_INDEX) == Some(&b'1'))
+    .then(|| ConfigPageSettings {
+        lines_per_page: LEGACY_CONFIG_PAGE_LINES,
+    })
+}
+
+/// Move the feature flags versions 1 and 2 kept in the uuid to the features
+/// bitfield. The uuid is chosen by the creator and can carry flags by accident,
+/// so only the flags backed by the tars or its accounts are kept.
+fn move_uuid_features(tars: &mut Tars, accounts: FeatureAccounts) {
+    let uuid = tars.data.uuid.as_bytes();
+    let flag = |feature_index: usize| uuid.get(feature_index) == Some(&b'1');
+    let reveal = flag(REVEAL_FEATURE_INDEX)
+        && tars.data.hidden_settings.is_some()
+        && tars.data.retain_authority;
+    let features = [
+        (
+            BLOCKLIST_FEATURE_INDEX,
+            flag(BLOCKLIST_FEATURE_INDEX) && accounts.blocklist,
+        ),
+        // Dropping a lock would let the authority change lines again
+        (
+            CONFIG_LOCKED_FEATURE_INDEX,
+            flag(CONFIG_LOCKED_FEATURE_INDEX),
+        ),
+        (REVEAL_FEATURE_INDEX, reveal),
+        (
+            STAGED_REVEAL_FEATURE_INDEX,
+            reveal && flag(STAGED_REVEAL_FEATURE_INDEX) && accounts.reveal_schedule,
+        ),
+    ];
+    for (feature_index, active) in features {
+        if active {
+            tars.set_feature(feature_index);
+        }
+    }
+}
+
+fn is_program_account(account: &AccountInfo) -> bool {
+    cmp_pubkeys(account.owner, &crate::id()) && !account.data_is_empty()
+}
+
+/// Decode an older tars header into the current one, returning it together
+/// with where the old config lines start.
+fn read_legacy_tars(
+    data: &[u8],
+    authority: &Pubkey,
+    accounts: FeatureAccounts,
+) -> Result<(Tars, usize)> {
+    if data.len() < LEGACY_CONFIG_ARRAY_START || data[..8] != Tars::discriminator() {
+        return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+    }
+    // Versioned accounts have the version byte in front of the authority,
+    // baseline ones start with the authority right after the discriminator.
+    let versioned = cmp_pubkeys(&Pubkey::new(&data[9..41]), authority);
+    if versioned && data[8] == TARS_VERSION {
+        return err!(TarsError::TarsAlreadyMigrated);
+    }
+    if versioned && (data[8] == 1 || data[8] == 2) {
+        let config_start = if data[8] == 1 {
+            V1_CONFIG_ARRAY_START
+        } else {
+            V2_CONFIG_ARRAY_START
+        };
+        if data.len() < config_start {
+            return Err(ErrorCode::AccountDidNotDeserialize.into());
+        }
+        let mut tars: Tars = if data[8] == 1 {
+            TarsV1::deserialize(&mut &data[9..])?.into()
+        } else {
+            TarsV2::deserialize(&mut &data[9..])?.into()
+        };
+        move_uuid_features(&mut tars, accounts);
+        Ok((tars, config_start))
+    } else if cmp_pubkeys(&Pubkey::new(&data[8..40]), authority) {
+        Ok((
+            BaselineTars::deserialize(&mut &data[8..])?.into(),
+            LEGACY_CONFIG_ARRAY_START,
+        ))
+    } else {
+        err!(TarsError::PublicKeyMismatch)
+    }
+}
+
+/// Move the config block of `config_len` bytes from `legacy_config_start` to
+/// `CONFIG_ARRAY_START` and write the migrated header in front of it.
+/// `data` must be at least as long as both the old and the new layout.
+fn write_migrated_tars(
+    data: &mut [u8],
+    tars: &Tars,
+    legacy_config_start: usize,
+    config_len: usize,
+) -> Result<()> {
+    // Config lines, bitmask and redemption bitmap move as one block.
+    data.copy_within(
+        legacy_config_start..legacy_config_start + config_len,
+        CONFIG_ARRAY_START,
+    );
+
+    let mut new_data = Tars::discriminator().try_to_vec().unwrap();
+    new_data.append(&mut tars.try_to_vec().unwrap());
+    data[..CONFIG_ARRAY_START].fill(0);
+    data[..new_data.len()].copy_from_slice(&new_data);
+    Ok(())
+}
+
+pub fn handle_migrate_tars(ctx: Context<MigrateTars>) -> Result<()> {
+    let tars_info = ctx.accounts.tars.to_account_info();
+    let authority = ctx.accounts.authority.key();
+
+    let accounts = FeatureAccounts {
+        blocklist: is_program_account(&ctx.accounts.blocklist),
+        reveal_schedule: is_program_account(&ctx.accounts.reveal_schedule),
+    };
+
+    let (tars, legacy_config_start) =
+        read_legacy_tars(&tars_info.data.borrow(), &authority, accounts)?;
+
+    let old_len = tars_info.data_len();
+    let config_len = old_len - legacy_config_start;
+    let new_len = CONFIG_ARRAY_START + config_len;
+
+    let rent_exempt_balance = Rent::get()?.minimum_balance(new_len);
+    if tars_info.lamports() < rent_exempt_balance {
+        invoke(
+            &system_instruction::transfer(
+                &ctx.accounts.payer.key(),
+                tars_info.key,
+                rent_exempt_balance - tars_info.lamports(),
+            ),
+            &[
+                ctx.accounts.payer.to_account_info(),
+                tars_info.clone(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+    }
+
+    if new_len > old_len {
+        tars_info.realloc(new_len, false)?;
+    }
+    write_migrated_tars(
+        &mut tars_info.data.borrow_mut(),
+        &tars,
+        legacy_config_start,
+        config_len,
+    )?;
+    if new_len < old_len {
+        tars_info.realloc(new_len, false)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn baseline_tars(authority: Pubkey) -> BaselineTars {
+        BaselineTars {
+            authority,
+            wallet: Pubkey::new_unique(),
+            token_mint: None,
+            items_redeemed: 3,
+            data: BaselineTarsData {
+                uuid: String::from("ABCDEF"),
+                price: 1_000_000,
+                symbol: String::from("TARS"),
+                seller_fee_basis_points: 500,
+                max_supply: 0,
+                is_mutable: true,
+                retain_authority: true,
+                go_live_date: Some(1_650_000_000),
+                end_settings: Some(BaselineEndSettings {
+                    end_setting_type: BaselineEndSettingType::Amount,
+                    number: 50,
+                }),
+                creators: vec![Creator {
+                    address: authority,
+                    verified: true,
+                    share: 100,
+                }],
+                hidden_settings: None,
+                whitelist_mint_settings: None,
+                items_available: 100,
+                gatekeeper: Some(GatekeeperConfig {
+                    gatekeeper_network: Pubkey::new_unique(),
+                    expire_on_use: true,
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn migrates_baseline_account() {
+        let authority = Pubkey::new_unique();
+        let baseline = baseline_tars(authority);
+        let gatekeeper_network = baseline
+            .data
+            .gatekeeper
+            .as_ref()
+            .unwrap()
+            .gatekeeper_network;
+
+        let mut data = Tars::discriminator().to_vec();
+        data.append(&mut baseline.try_to_vec().unwrap());
+        data.resize(LEGACY_CONFIG_ARRAY_START, 0);
+        let config_block: Vec<u8> = (0..500u32).map(|i| (i % 251) as u8).collect();
+        data.extend_from_slice(&config_block);
+
+        let (tars, legacy_config_start) =
+            read_legacy_tars(&data, &authority, FeatureAccounts::default()).unwrap();
+        assert_eq!(legacy_config_start, LEGACY_CONFIG_ARRAY_START);
+
+        let config_len = data.len() - legacy_config_start;
+        data.resize(CONFIG_ARRAY_START + config_len, 0);
+        write_migrated_tars(&mut data, &tars, legacy_config_start, config_len).unwrap();
+
+        let migrated = Tars::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(migrated.version, TARS_VERSION);
+        assert_eq!(migrated.authority, authority);
+        assert_eq!(migrated.items_redeemed, 3);
+        assert_eq!(migrated.data.items_available, 100);
+        assert_eq!(migrated.data.symbol, "TARS");
+        let end_settings = migrated.data.end_settings.unwrap();
+        assert_eq!(end_settings.end_date, None);
+        assert_eq!(end_settings.max_redeemed, Some(50));
+        let gatekeeper = migrated.data.gatekeeper.unwrap();
+        assert_eq!(gatekeeper.networks.len(), 1);
+        assert_eq!(
+            gatekeeper.networks[0].gatekeeper_network,
+            gatekeeper_network
+        );
+        assert!(gatekeeper.networks[0].expire_on_use);
+        assert!(migrated.data.config_line_template.is_none());
+        assert_eq!(migrated.proceeds, 0);
+        assert_eq!(migrated.features, 0);
+        assert_eq!(&data[CONFIG_ARRAY_START..], &config_block[..]);
+
+        assert!(read_legacy_tars(&data, &authority, FeatureAccounts::default()).is_err());
+    }
+
+    #[test]
+    fn rejects_short_v1_account() {
+        let authority = Pubkey::new_unique();
+        let mut data = Tars::discriminator().to_vec();
+        data.push(1);
+        data.extend_from_slice(authority.as_ref());
+        data.resize(LEGACY_CONFIG_ARRAY_START, 0);
+        assert!(read_legacy_tars(&data, &authority, FeatureAccounts::default()).is_err());
+    }
+
+    fn v2_account(authority: Pubkey, uuid: &str, hidden: bool) -> Vec<u8> {
+        let tars = TarsV2 {
+            authority,
+            wallet: Pubkey::new_unique(),
+            token_mint: None,
+            items_redeemed: 0,
+            data: TarsDataV2 {
+                uuid: String::from(uuid),
+                retain_authority: true,
+                items_available: 10,
+                hidden_settings: hidden.then(HiddenSettings::default),
+                ..TarsDataV2::default()
+            },
+            last_mint_slot: 0,
+            mints_in_slot: 0,
+            proceeds: 0,
+        };
+        let mut data = Tars::discriminator().to_vec();
+        data.push(2);
+        data.append(&mut tars.try_to_vec().unwrap());
+        data.resize(V2_CONFIG_ARRAY_START, 0);
+        data
+    }
+
+    #[test]
+    fn drops_uuid_flags_without_their_accounts() {
+        let authority = Pubkey::new_unique();
+        let data = v2_account(authority, "111111", false);
+
+        let (tars, legacy_config_start) =
+            read_legacy_tars(&data, &authority, FeatureAccounts::default()).unwrap();
+        assert_eq!(legacy_config_start, V2_CONFIG_ARRAY_START);
+        assert_eq!(tars.version, TARS_VERSION);
+        assert!(tars.is_feature_active(CONFIG_LOCKED_FEATURE_INDEX));
+        assert!(!tars.is_feature_active(BLOCKLIST_FEATURE_INDEX));
+        assert!(!tars.is_feature_active(REVEAL_FEATURE_INDEX));
+        assert!(!tars.is_feature_active(STAGED_REVEAL_FEATURE_INDEX));
+        // the config pages flag laid out the lines in pages of 32
+        assert_eq!(tars.data.config_pages.unwrap().lines_per_page, 32);
+    }
+
+    #[test]
+    fn keeps_uuid_flags_backed_by_their_accounts() {
+        let authority = Pubkey::new_unique();
+        let data = v2_account(authority, "010011", true);
+        let accounts = FeatureAccounts {
+            blocklist: true,
+            reveal_schedule: true,
+        };
+
+        let (tars, _) = read_legacy_tars(&data, &authority, accounts).unwrap();
+        assert!(tars.data.config_pages.is_none());
+        assert!(tars.is_feature_active(BLOCKLIST_FEATURE_INDEX));
+        assert!(!tars.is_feature_active(CONFIG_LOCKED_FEATURE_INDEX));
+        assert!(tars.is_feature_active(REVEAL_FEATURE_INDEX));
+        assert!(tars.is_feature_active(STAGED_REVEAL_FEATURE_INDEX));
+    }
+}
@@ -1,20 +1,17 @@
-use std::{cell::RefMut, ops::Deref};
+use std::cell::RefMut;
 
 use anchor_lang::prelude::*;
 use anchor_spl::token::Token;
 use arrayref::array_ref;
 use mpl_token_metadata::{
+    assertions::collection::assert_master_edition,
     instruction::{
         create_master_edition_v3, create_metadata_accounts_v2, update_metadata_accounts_v2,
     },
-    state::{MAX_NAME_LENGTH, MAX_URI_LENGTH},
-};
-use solana_gateway::{
-    state::{GatewayTokenAccess, InPlaceGatewayToken},
-    Gateway,
 };
 use solana_program::{
     clock::Clock,
+    ed25519_program,
     program::{invoke, invoke_signed},
     serialize_utils::{read_pubkey, read_u16},
     system_instruction, sysvar,
@@ -23,12 +20,13 @@ use solana_program::{
 
 use crate::{
     constants::{
-        A_TOKEN, BLOCK_HASHES, COLLECTIONS_FEATURE_INDEX, CONFIG_ARRAY_START,
-        CONFIG_LINE_SIZE, CUPCAKE_ID, EXPIRE_OFFSET, GUMDROP_ID, PREFIX,
+        A_TOKEN, BLOCKLIST_FEATURE_INDEX, BLOCK_HASHES, COLLECTIONS_FEATURE_INDEX,
+        CONFIG_ARRAY_START, CONFIG_LOCKED_FEATURE_INDEX, CUPCAKE_ID, GUMDROP_ID, PREFIX,
+        REVEAL_FEATURE_INDEX, TARS_VERSION,
     },
+    get_config_count,
     utils::*,
-    TarsError, Tars, TarsData, ConfigLine, EndSettingType, WhitelistMintMode,
-    WhitelistMintSettings,
+    BurnToMintSettings, ConfigLine, Tars, TarsError, WhitelistMintMode,
 };
 
 /// Mint a new NFT pseudo-randomly from the config array.
@@ -37,7 +35,8 @@ use crate::{
 pub struct MintNFT<'info> {
     #[account(
     mut,
-    has_one = wallet
+    has_one = wallet,
+    constraint = tars.version == TARS_VERSION @ TarsError::TarsVersionMismatch
     )]
     tars: Box<Account<'info, Tars>>,
     /// CHECK: account constraints checked in account trait
@@ -74,9 +73,9 @@ pub struct MintNFT<'info> {
     /// CHECK: account constraints checked in account trait
     #[account(address = sysvar::instructions::id())]
     instruction_sysvar_account: UncheckedAccount<'info>,
-    // > Only needed if tars has a gatekeeper
+    // > Only needed if tars has a gatekeeper, repeated for every network in order
     // gateway_token
-    // > Only needed if tars has a gatekeeper and it has expire_on_use set to true:
+    // > Only needed if the network has expire_on_use set to true:
     // gateway program
     // network_expire_feature
     // > Only needed if tars has whitelist_mint_settings
@@ -84,9 +83,35 @@ pub struct MintNFT<'info> {
     // > Only needed if tars has whitelist_mint_settings and mode is BurnEveryTime
     // whitelist_token_mint
     // whitelist_burn_authority
+    // > Only needed if tars has collection_gate
+    // gate_token_account
+    // gate_metadata
+    // > Only needed if tars has collection_gate with uses_per_nft
+    // gate_usage
+    // > Only needed if tars has burn_to_mint, repeated burn_count times
+    // burn_metadata
+    // burn_edition
+    // burn_token_account
+    // burn_mint
+    // > Only needed if tars has a blocklist
+    // blocklist
+    // > Only needed if tars has rate_limit with wallet_cooldown
+    // wallet_mint_record
+    // > Only needed if tars has authorized_signer
+    // voucher_record
+    // > Only needed if tars uses config pages, the page its page cursor points at
+    // config_page
+    // > Only needed if tars has a committed reveal
+    // mint_receipt
     // > Only needed if tars has token mint
     // token_account_info
     // transfer_authority_info
+    // > Only needed if tars has holder_discounts, one slot per discount in order,
+    // > either the tars program id when the discount isn't claimed or
+    // discount_token_account
+    // discount_metadata
+    // > Only needed if the discount has uses_per_nft
+    // discount_usage
 }
 
 pub fn handle_mint_nft<'info>(
@@ -181,6 +206,7 @@ pub fn handle_mint_nft<'info>(
                 &anchor_lang::solana_program::system_program::ID,
             )
             && !cmp_pubkeys(&program_id, &A_TOKEN)
+            && !cmp_pubkeys(&program_id, &ed25519_program::id())
         {
             msg!("Transaction had ix with program id {}", program_id);
             // punish_bots(
@@ -195,133 +221,26 @@ pub fn handle_mint_nft<'info>(
         }
     }
 
-    let mut price = tars.data.price;
+    let base_price = match &tars.data.burn_to_mint {
+        Some(BurnToMintSettings {
+            price: Some(burn_price),
+            ..
+        }) => *burn_price,
+        _ => tars.data.price,
+    };
+    let mut price = base_price;
     if let Some(es) = &tars.data.end_settings {
-        match es.end_setting_type {
-            EndSettingType::Date => {
-                if clock.unix_timestamp > es.number as i64
-                    && !cmp_pubkeys(&ctx.accounts.payer.key(), &tars.authority)
-                {
-                    // punish_bots(
-                    //     TarsError::TarsNotLive,
-                    //     payer.to_account_info(),
-                    //     ctx.accounts.tars.to_account_info(),
-                    //     ctx.accounts.system_program.to_account_info(),
-                    //     BOT_FEE,
-                    // )?;
-                    return Err(error!(TarsError::TarsNotLiveForWL));
-                    // return Ok(());
-                }
-            }
-            EndSettingType::Amount => {
-                if tars.items_redeemed >= es.number {
-                    if !cmp_pubkeys(&ctx.accounts.payer.key(), &tars.authority) {
-                        // punish_bots(
-                        //     TarsError::TarsEmpty,
-                        //     payer.to_account_info(),
-                        //     ctx.accounts.tars.to_account_info(),
-                        //     ctx.accounts.system_program.to_account_info(),
-                        //     BOT_FEE,
-                        // )?;
-                        return Err(error!(TarsError::TarsNotLiveForWL));
-                        // return Ok(());
-                    }
-                    return err!(TarsError::TarsEmpty);
-                }
-            }
-        }
+        assert_sale_not_ended(es, tars, &payer.key(), clock.unix_timestamp)?;
     }
     let mut remaining_accounts_counter: usize = 0;
     if let Some(gatekeeper) = &tars.data.gatekeeper {
-        if ctx.remaining_accounts.len() <= remaining_accounts_counter {
-            // punish_bots(
-            //     TarsError::GatewayTokenMissing,
-            //     payer.to_account_info(),
-            //     ctx.accounts.tars.to_account_info(),
-            //     ctx.accounts.system_program.to_account_info(),
-            //     BOT_FEE,
-            // )?;
-            // return Ok(());
-            return Err(error!(TarsError::TarsNotLiveForWL));
-        }
-        let gateway_token_info = &ctx.remaining_accounts[remaining_accounts_counter];
-
-        remaining_accounts_counter += 1;
-
-        // Eval function used in the gateway CPI
-        let eval_function =
-            |token: &InPlaceGatewayToken<&[u8]>| match (&tars.data, token.expire_time()) {
-                (
-                    TarsData {
-                        go_live_date: Some(go_live_date),
-                        whitelist_mint_settings: Some(WhitelistMintSettings { presale, .. }),
-                        ..
-                    },
-                    Some(expire_time),
-                ) if !*presale && expire_time < go_live_date + EXPIRE_OFFSET => {
-                    msg!(
-                        "Invalid gateway token: calculated creation time {} and go_live_date {}",
-                        expire_time - EXPIRE_OFFSET,
-                        go_live_date
-                    );
-                    Err(error!(TarsError::GatewayTokenExpireTimeInvalid).into())
-                }
-                _ => Ok(()),
-            };
-
-        if gatekeeper.expire_on_use {
-            if ctx.remaining_accounts.len() <= remaining_accounts_counter {
-                return err!(TarsError::GatewayAppMissing);
-            }
-
-            let gateway_app = &ctx.remaining_accounts[remaining_accounts_counter];
-            remaining_accounts_counter += 1;
-
-            if ctx.remaining_accounts.len() <= remaining_accounts_counter {
-                return err!(TarsError::NetworkExpireFeatureMissing);
-            }
-            let network_expire_feature = &ctx.remaining_accounts[remaining_accounts_counter];
-            remaining_accounts_counter += 1;
-
-            if Gateway::verify_and_expire_token_with_eval(
-                gateway_app.clone(),
-                gateway_token_info.clone(),
-                payer.deref().clone(),
-                &gatekeeper.gatekeeper_network,
-                network_expire_feature.clone(),
-                eval_function,
-            )
-            .is_err()
-            {
-                // punish_bots(
-                //     TarsError::GatewayProgramError,
-                //     payer.to_account_info(),
-                //     ctx.accounts.tars.to_account_info(),
-                //     ctx.accounts.system_program.to_account_info(),
-                //     BOT_FEE,
-                // )?;
-                return Err(error!(TarsError::TarsNotLiveForWL));
-                // return Ok(());
-            }
-        } else if Gateway::verify_gateway_token_with_eval(
-            gateway_token_info,
-            &payer.key(),
-            &gatekeeper.gatekeeper_network,
-            None,
-            eval_function,
-        )
-        .is_err()
-        {
-            // punish_bots(
-            //     TarsError::GatewayProgramError,
-            //     payer.to_account_info(),
-            //     ctx.accounts.tars.to_account_info(),
-            //     ctx.accounts.system_program.to_account_info(),
-            //     BOT_FEE,
-            // )?;
-            return Err(error!(TarsError::TarsNotLiveForWL));
-            // return Ok(());
-        }
+        verify_gatekeepers(
+            &tars.data,
+            gatekeeper,
+            ctx.remaining_accounts,
+            &mut remaining_accounts_counter,
+            &payer.to_account_info(),
+        )?;
     }
 
     if let Some(ws) = &tars.data.whitelist_mint_settings {
@@ -484,6 +403,79 @@ pub fn handle_mint_nft<'info>(
         }
     }
 
+    if let Some(gate) = &tars.data.collection_gate {
+        if ctx.remaining_accounts.len() < remaining_accounts_counter + 2 {
+            return err!(TarsError::MissingCollectionNft);
+        }
+        let gate_token_account = &ctx.remaining_accounts[remaining_accounts_counter];
+        remaining_accounts_counter += 1;
+        let gate_metadata = &ctx.remaining_accounts[remaining_accounts_counter];
+        remaining_accounts_counter += 1;
+
+        let nft_mint = assert_collection_nft(
+            gate_token_account,
+            gate_metadata,
+            &payer.key(),
+            &gate.collection,
+        )?
+        .mint;
+
+        if let Some(uses_per_nft) = gate.uses_per_nft {
+            if ctx.remaining_accounts.len() <= remaining_accounts_counter {
+                return err!(TarsError::MissingCollectionNft);
+            }
+            let gate_usage = &ctx.remaining_accounts[remaining_accounts_counter];
+            remaining_accounts_counter += 1;
+
+            record_nft_usage(NftUsageParams {
+                usage: gate_usage.clone(),
+                prefix: b"collection-gate",
+                tars: tars.key(),
+                nft_mint,
+                max_uses: uses_per_nft,
+                payer: payer.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+            })?;
+        }
+    }
+
+    if let Some(burn_to_mint) = &tars.data.burn_to_mint {
+        for _ in 0..burn_to_mint.burn_count {
+            if ctx.remaining_accounts.len() < remaining_accounts_counter + 4 {
+                return err!(TarsError::MissingBurnNft);
+            }
+            let burn_metadata = &ctx.remaining_accounts[remaining_accounts_counter];
+            remaining_accounts_counter += 1;
+            let burn_edition = &ctx.remaining_accounts[remaining_accounts_counter];
+            remaining_accounts_counter += 1;
+            let burn_token_account = &ctx.remaining_accounts[remaining_accounts_counter];
+            remaining_accounts_counter += 1;
+            let burn_mint = &ctx.remaining_accounts[remaining_accounts_counter];
+            remaining_accounts_counter += 1;
+
+            let metadata = assert_collection_nft(
+                burn_token_account,
+                burn_metadata,
+                &payer.key(),
+                &burn_to_mint.collection,
+            )?;
+            assert_keys_equal(&burn_mint.key(), &metadata.mint)?;
+            assert_master_edition(&metadata, burn_edition)?;
+
+            // Token metadata has no burn instruction we can use, so burning the
+            // token is what takes the NFT out of circulation.
+            spl_token_burn(TokenBurnParams {
+                mint: burn_mint.clone(),
+                source: burn_token_account.clone(),
+                amount: 1,
+                authority: payer.to_account_info(),
+                authority_signer_seeds: None,
+                token_program: token_program.to_account_info(),
+            })?;
+        }
+    }
+
     if tars.items_redeemed >= tars.data.items_available {
         // punish_bots(
         //     TarsError::TarsEmpty,
@@ -496,6 +488,118 @@ pub fn handle_mint_nft<'info>(
         return Err(error!(TarsError::TarsNotLiveForWL));
     }
 
+    if tars.is_feature_active(BLOCKLIST_FEATURE_INDEX) {
+        if ctx.remaining_accounts.len() <= remaining_accounts_counter {
+            return err!(TarsError::MissingBlocklist);
+        }
+        let blocklist = &ctx.remaining_accounts[remaining_accounts_counter];
+        remaining_accounts_counter += 1;
+        assert_not_blocked(blocklist, &tars.key(), &payer.key())?;
+    }
+
+    if let Some(rate_limit) = tars.data.rate_limit.clone() {
+        let clock = Clock::get()?;
+        if let Some(wallet_cooldown) = rate_limit.wallet_cooldown {
+            if ctx.remaining_accounts.len() <= remaining_accounts_counter {
+                return err!(TarsError::MissingWalletMintRecord);
+            }
+            let wallet_mint_record = &ctx.remaining_accounts[remaining_accounts_counter];
+            remaining_accounts_counter += 1;
+
+            record_wallet_mint(WalletMintParams {
+                wallet_mint_record: wallet_mint_record.clone(),
+                tars: tars.key(),
+                wallet: payer.to_account_info(),
+                cooldown: wallet_cooldown,
+                system_program: ctx.accounts.system_program.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+                now: clock.unix_timestamp,
+            })?;
+        }
+
+        if let Some(max_mints_per_slot) = rate_limit.max_mints_per_slot {
+            if tars.last_mint_slot != clock.slot {
+                tars.last_mint_slot = clock.slot;
+                tars.mints_in_slot = 0;
+            }
+            if tars.mints_in_slot >= max_mints_per_slot {
+                return err!(TarsError::SlotMintLimitReached);
+            }
+            tars.mints_in_slot = tars
+                .mints_in_slot
+                .checked_add(1)
+                .ok_or(TarsError::NumericalOverflowError)?;
+        }
+    }
+
+    if let Some(authorized_signer) = tars.data.authorized_signer {
+        if ctx.remaining_accounts.len() <= remaining_accounts_counter {
+            return err!(TarsError::MissingMintVoucher);
+        }
+        let voucher_record = &ctx.remaining_accounts[remaining_accounts_counter];
+        remaining_accounts_counter += 1;
+
+        price = use_mint_voucher(MintVoucherParams {
+            instruction_sysvar: &instruction_sysvar_account_info,
+            voucher_record: voucher_record.clone(),
+            authorized_signer,
+            tars: tars.key(),
+            buyer: payer.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+            now: Clock::get()?.unix_timestamp,
+        })?;
+    }
+
+    let mut config_page = None;
+    if config_page_lines(&tars.data).is_some() {
+        if ctx.remaining_accounts.len() <= remaining_accounts_counter {
+            return err!(TarsError::MissingConfigPage);
+        }
+        config_page = Some(&ctx.remaining_accounts[remaining_accounts_counter]);
+        remaining_accounts_counter += 1;
+    }
+
+    if tars.is_feature_active(REVEAL_FEATURE_INDEX) {
+        if ctx.remaining_accounts.len() <= remaining_accounts_counter {
+            return err!(TarsError::MissingMintReceipt);
+        }
+        let mint_receipt = &ctx.remaining_accounts[remaining_accounts_counter];
+        remaining_accounts_counter += 1;
+
+        record_mint_receipt(MintReceiptParams {
+            mint_receipt: mint_receipt.clone(),
+            tars: tars.key(),
+            mint_number: tars.items_redeemed,
+            metadata: ctx.accounts.metadata.key(),
+            payer: payer.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        })?;
+    }
+
+    if !tars.data.holder_discounts.is_empty() {
+        // Discount proofs come after the token payment accounts.
+        let mut discount_counter = remaining_accounts_counter;
+        if tars.token_mint.is_some() {
+            discount_counter += 2;
+        }
+
+        price = apply_holder_discounts(HolderDiscountParams {
+            discounts: &tars.data.holder_discounts,
+            proofs: ctx
+                .remaining_accounts
+                .get(discount_counter..)
+                .unwrap_or_default(),
+            tars: tars.key(),
+            buyer: payer.to_account_info(),
+            base_price,
+            price,
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        })?;
+    }
+
     if let Some(mint) = tars.token_mint {
         let token_account_info = &ctx.remaining_accounts[remaining_accounts_counter];
         remaining_accounts_counter += 1;
@@ -532,6 +636,11 @@ pub fn handle_mint_nft<'info>(
         )?;
     }
 
+    tars.proceeds = tars
+        .proceeds
+        .checked_add(price)
+        .ok_or(TarsError::NumericalOverflowError)?;
+
     let data = recent_slothashes.data.borrow();
     let most_recent = array_ref![data, 12, 8];
 
@@ -540,8 +649,12 @@ pub fn handle_mint_nft<'info>(
         .checked_rem(tars.data.items_available)
         .ok_or(TarsError::NumericalOverflowError)? as usize;
 
-    let config_line = get_config_line(tars, modded, tars.items_redeemed)?;
+    let config_line = get_config_line(tars, config_page, modded, tars.items_redeemed)?;
 
+    // Lines can't change once minting started
+    if tars.items_redeemed == 0 {
+        tars.set_feature(CONFIG_LOCKED_FEATURE_INDEX);
+    }
     tars.items_redeemed = tars
         .items_redeemed
         .checked_add(1)
@@ -630,6 +743,11 @@ pub fn handle_mint_nft<'info>(
     if !tars.data.retain_authority {
         new_update_authority = Some(ctx.accounts.update_authority.key());
     }
+    // Until revealed the tars keeps updating the NFT
+    let reveal = tars.is_feature_active(REVEAL_FEATURE_INDEX);
+    if reveal {
+        new_update_authority = None;
+    }
     invoke_signed(
         &update_metadata_accounts_v2(
             ctx.accounts.token_metadata_program.key(),
@@ -638,7 +756,7 @@ pub fn handle_mint_nft<'info>(
             new_update_authority,
             None,
             Some(true),
-            if !tars.data.is_mutable {
+            if !tars.data.is_mutable && !reveal {
                 Some(false)
             } else {
                 None
@@ -658,6 +776,7 @@ pub fn handle_mint_nft<'info>(
 pub fn get_good_index(
     arr: &mut RefMut<&mut [u8]>,
     items_available: usize,
+    line_size: usize,
     index: usize,
     pos: bool,
 ) -> Result<(usize, bool)> {
@@ -666,7 +785,7 @@ pub fn get_good_index(
     let mut found = false;
     let bit_mask_vec_start = CONFIG_ARRAY_START
         + 4
-        + (items_available) * CONFIG_LINE_SIZE
+        + (items_available) * line_size
         + 4
         + items_available
             .checked_div(8)
@@ -729,6 +848,7 @@ pub fn get_good_index(
 
 pub fn get_config_line(
     a: &Account<'_, Tars>,
+    config_page: Option<&AccountInfo>,
     index: usize,
     mint_number: u64,
 ) -> Result<ConfigLine> {
@@ -738,56 +858,46 @@ pub fn get_config_line(
             uri: hs.uri.clone(),
         });
     }
+    if let (Some(config_page), Some(lines_per_page)) = (config_page, config_page_lines(&a.data)) {
+        return get_config_page_line(a, config_page, lines_per_page, index);
+    }
     let a_info = a.to_account_info();
 
+    let line_size = config_line_size(&a.data);
     let mut arr = a_info.data.borrow_mut();
 
-    let (mut index_to_use, good) =
-        get_good_index(&mut arr, a.data.items_available as usize, index, true)?;
+    // Lines added by growing the tars must be loaded before minting resumes.
+    if get_config_count(&arr)? < a.data.items_available as usize {
+        return err!(TarsError::TarsNotFullyLoaded);
+    }
+
+    let (mut index_to_use, good) = get_good_index(
+        &mut arr,
+        a.data.items_available as usize,
+        line_size,
+        index,
+        true,
+    )?;
     if !good {
-        let (index_to_use_new, good_new) =
-            get_good_index(&mut arr, a.data.items_available as usize, index, false)?;
+        let (index_to_use_new, good_new) = get_good_index(
+            &mut arr,
+            a.data.items_available as usize,
+            line_size,
+            index,
+            false,
+        )?;
         index_to_use = index_to_use_new;
         if !good_new {
             return err!(TarsError::CannotFindUsableConfigLine);
         }
     }
 
-    if arr[CONFIG_ARRAY_START + 4 + index_to_use * (CONFIG_LINE_SIZE)] == 1 {
+    if arr[CONFIG_ARRAY_START + 4 + index_to_use * line_size] == 1 {
         return err!(TarsError::CannotFindUsableConfigLine);
     }
 
-    let data_array = &mut arr[CONFIG_ARRAY_START + 4 + index_to_use * (CONFIG_LINE_SIZE)
-        ..CONFIG_ARRAY_START + 4 + (index_to_use + 1) * (CONFIG_LINE_SIZE)];
-
-    let mut name_vec = Vec::with_capacity(MAX_NAME_LENGTH);
-    let mut uri_vec = Vec::with_capacity(MAX_URI_LENGTH);
-
-    #[allow(clippy::needless_range_loop)]
-    for i in 4..4 + MAX_NAME_LENGTH {
-        if data_array[i] == 0 {
-            break;
-        }
-        name_vec.push(data_array[i])
-    }
-
-    #[allow(clippy::needless_range_loop)]
-    for i in 8 + MAX_NAME_LENGTH..8 + MAX_NAME_LENGTH + MAX_URI_LENGTH {
-        if data_array[i] == 0 {
-            break;
-        }
-        uri_vec.push(data_array[i])
-    }
-    let config_line: ConfigLine = ConfigLine {
-        name: match String::from_utf8(name_vec) {
-            Ok(val) => val,
-            Err(_) => return err!(TarsError::InvalidString),
-        },
-        uri: match String::from_utf8(uri_vec) {
-            Ok(val) => val,
-            Err(_) => return err!(TarsError::InvalidString),
-        },
-    };
+    let data_array = &arr[CONFIG_ARRAY_START + 4 + index_to_use * line_size
+        ..CONFIG_ARRAY_START + 4 + (index_to_use + 1) * line_size];
 
-    Ok(config_line)
+    parse_config_line(data_array, &a.data.config_line_template)
 }
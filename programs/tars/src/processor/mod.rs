@@ -1,15 +1,31 @@
 pub mod add_config_lines;
+pub mod blocklist;
 pub mod collection;
+pub mod config_lock;
+pub mod create_config_page;
 pub mod initialize;
+pub mod migrate_tars;
 pub mod mint;
+pub mod remove_config_lines;
+pub mod replace_config_lines;
+pub mod resize_tars;
+pub mod reveal;
 pub mod update;
 pub mod withdraw;
 pub mod whitelist_mint;
 
 pub use add_config_lines::*;
+pub use blocklist::*;
 pub use collection::*;
+pub use config_lock::*;
+pub use create_config_page::*;
 pub use initialize::*;
+pub use migrate_tars::*;
 pub use mint::*;
+pub use remove_config_lines::*;
+pub use replace_config_lines::*;
+pub use resize_tars::*;
+pub use reveal::*;
 pub use update::*;
 pub use withdraw::*;
 pub use whitelist_mint::*;
\ No newline at end of file
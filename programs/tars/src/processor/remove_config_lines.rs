@@ -0,0 +1,131 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    assert_config_page, bit_mask_start, clear_page_line, cmp_pubkeys, config_line_size,
+    config_page_lines,
+    constants::{CONFIG_ARRAY_START, TARS_VERSION},
+    get_config_count, is_config_locked, is_page_line_set, split_config_page, Tars, TarsError,
+};
+
+/// Clear `count` config lines of the tars from `index`, so they can be added again.
+#[derive(Accounts)]
+pub struct RemoveConfigLines<'info> {
+    #[account(mut, has_one = authority, constraint = tars.version == TARS_VERSION @ TarsError::TarsVersionMismatch)]
+    tars: Account<'info, Tars>,
+    authority: Signer<'info>,
+    // > Only needed if tars uses config pages, repeated for every page the lines span
+    // config_page
+}
+
+pub fn handle_remove_config_lines(
+    ctx: Context<RemoveConfigLines>,
+    index: u32,
+    count: u32,
+) -> Result<()> {
+    let tars = &ctx.accounts.tars;
+    if tars.data.hidden_settings.is_some() {
+        return err!(TarsError::HiddenSettingsConfigsDoNotHaveConfigLines);
+    }
+    if is_config_locked(tars) {
+        return err!(TarsError::ConfigLocked);
+    }
+    let start = index as usize;
+    let end = start
+        .checked_add(count as usize)
+        .ok_or(TarsError::NumericalOverflowError)?;
+    if end > tars.data.items_available as usize {
+        return err!(TarsError::IndexGreaterThanLength);
+    }
+    let line_size = config_line_size(&tars.data);
+
+    let account = tars.to_account_info();
+    if let Some(lines_per_page) = config_page_lines(&tars.data) {
+        let unloaded = remove_config_page_lines(
+            &tars.key(),
+            ctx.remaining_accounts,
+            lines_per_page,
+            start,
+            end,
+            line_size,
+        )?;
+        let mut data = account.data.borrow_mut();
+        let loaded = get_config_count(&data)? - unloaded;
+        data[CONFIG_ARRAY_START..CONFIG_ARRAY_START + 4]
+            .copy_from_slice(&(loaded as u32).to_le_bytes());
+        return Ok(());
+    }
+
+    let mut loaded = get_config_count(&account.data.borrow_mut())?;
+    let mut data = account.data.borrow_mut();
+    data[CONFIG_ARRAY_START + 4 + start * line_size..CONFIG_ARRAY_START + 4 + end * line_size]
+        .fill(0);
+
+    let bit_mask_start = bit_mask_start(tars.data.items_available as usize, line_size);
+    for position in start..end {
+        let mask = 1u8 << (7 - position % 8);
+        if data[bit_mask_start + position / 8] & mask != 0 {
+            data[bit_mask_start + position / 8] &= !mask;
+            loaded -= 1;
+        }
+    }
+
+    data[CONFIG_ARRAY_START..CONFIG_ARRAY_START + 4]
+        .copy_from_slice(&(loaded as u32).to_le_bytes());
+    Ok(())
+}
+
+// Clears the lines from `start` to `end` of a tars using config pages, in the
+// pages passed as remaining accounts, one for every page the lines span, in
+// order, or its address if it was never created. Returns the number of lines
+// that were loaded.
+pub fn remove_config_page_lines(
+    tars: &Pubkey,
+    config_pages: &[AccountInfo],
+    lines_per_page: usize,
+    start: usize,
+    end: usize,
+    line_size: usize,
+) -> Result<usize> {
+    let mut unloaded = 0;
+    let mut position = start;
+    for config_page_info in config_pages {
+        if position >= end {
+            break;
+        }
+        let page_index = position / lines_per_page;
+        // Pages that were never created hold no lines, their address is enough
+        if config_page_info.data_is_empty() {
+            let (config_page_key, _) = Pubkey::find_program_address(
+                &[
+                    b"config-page".as_ref(),
+                    tars.as_ref(),
+                    &(page_index as u32).to_le_bytes(),
+                ],
+                &crate::id(),
+            );
+            if !cmp_pubkeys(&config_page_key, config_page_info.key) {
+                return err!(TarsError::InvalidConfigPage);
+            }
+            position = end.min((page_index + 1) * lines_per_page);
+            continue;
+        }
+        assert_config_page(config_page_info, tars, page_index)?;
+
+        let mut data = config_page_info.data.borrow_mut();
+        let (loaded, _, page_lines) = split_config_page(&mut data, lines_per_page);
+        while position < end && position / lines_per_page == page_index {
+            let slot = position % lines_per_page;
+            let line_start = slot * line_size;
+            page_lines[line_start..line_start + line_size].fill(0);
+            if is_page_line_set(loaded, slot) {
+                unloaded += 1;
+            }
+            clear_page_line(loaded, slot);
+            position += 1;
+        }
+    }
+    if position < end {
+        return err!(TarsError::MissingConfigPage);
+    }
+    Ok(unloaded)
+}
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    assert_config_page, bit_mask_start, config_line_size, config_page_lines,
+    constants::{CONFIG_ARRAY_START, TARS_VERSION},
+    is_config_locked, is_page_line_set, serialize_config_line, split_config_page, ConfigLine, Tars,
+    TarsError,
+};
+
+/// Overwrite already added config lines of the tars from `index`.
+#[derive(Accounts)]
+pub struct ReplaceConfigLines<'info> {
+    #[account(mut, has_one = authority, constraint = tars.version == TARS_VERSION @ TarsError::TarsVersionMismatch)]
+    tars: Account<'info, Tars>,
+    authority: Signer<'info>,
+    // > Only needed if tars uses config pages, repeated for every page the lines span
+    // config_page
+}
+
+pub fn handle_replace_config_lines(
+    ctx: Context<ReplaceConfigLines>,
+    index: u32,
+    config_lines: Vec<ConfigLine>,
+) -> Result<()> {
+    let tars = &ctx.accounts.tars;
+    if tars.data.hidden_settings.is_some() {
+        return err!(TarsError::HiddenSettingsConfigsDoNotHaveConfigLines);
+    }
+    // The first mint locks the lines, so none of them can be redeemed yet.
+    if is_config_locked(tars) {
+        return err!(TarsError::ConfigLocked);
+    }
+    let start = index as usize;
+    let end = start
+        .checked_add(config_lines.len())
+        .ok_or(TarsError::NumericalOverflowError)?;
+    if end > tars.data.items_available as usize {
+        return err!(TarsError::IndexGreaterThanLength);
+    }
+    let line_size = config_line_size(&tars.data);
+    let mut lines = Vec::with_capacity(config_lines.len());
+    for line in &config_lines {
+        lines.push(serialize_config_line(
+            line,
+            &tars.data.config_line_template,
+        )?);
+    }
+
+    if let Some(lines_per_page) = config_page_lines(&tars.data) {
+        let mut lines = lines.iter();
+        let mut position = start;
+        for config_page_info in ctx.remaining_accounts {
+            if position >= end {
+                break;
+            }
+            let page_index = position / lines_per_page;
+            assert_config_page(config_page_info, &tars.key(), page_index)?;
+
+            let mut data = config_page_info.data.borrow_mut();
+            let (loaded, _, page_lines) = split_config_page(&mut data, lines_per_page);
+            while position < end && position / lines_per_page == page_index {
+                let slot = position % lines_per_page;
+                if !is_page_line_set(loaded, slot) {
+                    return err!(TarsError::ConfigLineNotLoaded);
+                }
+                let line_start = slot * line_size;
+                page_lines[line_start..line_start + line_size]
+                    .copy_from_slice(lines.next().unwrap());
+                position += 1;
+            }
+        }
+        if position < end {
+            return err!(TarsError::MissingConfigPage);
+        }
+        return Ok(());
+    }
+
+    let account = tars.to_account_info();
+    let mut data = account.data.borrow_mut();
+    let items_available = tars.data.items_available as usize;
+    let bit_mask_start = bit_mask_start(items_available, line_size);
+    for (position, line) in (start..end).zip(lines.iter()) {
+        let mask = 1u8 << (7 - position % 8);
+        if data[bit_mask_start + position / 8] & mask == 0 {
+            return err!(TarsError::ConfigLineNotLoaded);
+        }
+        let line_start = CONFIG_ARRAY_START + 4 + position * line_size;
+        data[line_start..line_start + line_size].copy_from_slice(line);
+    }
+    Ok(())
+}
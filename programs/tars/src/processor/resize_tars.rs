@@ -0,0 +1,276 @@
+use anchor_lang::prelude::*;
+use solana_program::{
+    entrypoint::MAX_PERMITTED_DATA_INCREASE, program::invoke, system_instruction,
+};
+
+use crate::{
+    bit_mask_start, config_line_size, config_page_lines,
+    constants::{CONFIG_ARRAY_START, TARS_VERSION},
+    get_config_count, get_space_for_tars, is_config_locked, redeemed_start,
+    remove_config_page_lines, Tars, TarsError,
+};
+
+/// Change the number of items available of a tars with config lines. Once the
+/// lines are locked, the tars can still grow to take new lines.
+#[derive(Accounts)]
+pub struct ResizeTars<'info> {
+    #[account(
+    mut,
+    has_one = authority,
+    constraint = tars.version == TARS_VERSION @ TarsError::TarsVersionMismatch
+    )]
+    tars: Account<'info, Tars>,
+    #[account(mut)]
+    authority: Signer<'info>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+    // > Only needed if tars uses config pages and shrinks, repeated for every page the removed lines span
+    // config_page
+}
+
+pub fn handle_resize_tars(ctx: Context<ResizeTars>, items_available: u64) -> Result<()> {
+    let tars = &mut ctx.accounts.tars;
+    if tars.data.hidden_settings.is_some() {
+        return err!(TarsError::HiddenSettingsConfigsDoNotHaveConfigLines);
+    }
+    let old_items = tars.data.items_available as usize;
+    let new_items = items_available as usize;
+    // Lines can only be taken away before anything was minted from them.
+    if new_items < old_items && tars.items_redeemed > 0 {
+        return err!(TarsError::CannotShrinkDuringMint);
+    }
+    // Locked lines stay, a locked tars can only grow to append new ones.
+    if new_items < old_items && is_config_locked(tars) {
+        return err!(TarsError::ConfigLocked);
+    }
+    let mut new_data = tars.data.clone();
+    new_data.items_available = items_available;
+    let old_len = get_space_for_tars(tars.data.clone())?;
+    let new_len = get_space_for_tars(new_data)?;
+    let tars_info = tars.to_account_info();
+    let current_len = tars_info.data_len();
+    if new_len > current_len + MAX_PERMITTED_DATA_INCREASE {
+        return err!(TarsError::ResizeTooLarge);
+    }
+
+    let line_size = config_line_size(&tars.data);
+    // Config pages are created on their own, the tars only keeps the count of
+    // loaded lines and the bitmask of pages used by minting.
+    let lines_per_page = config_page_lines(&tars.data);
+    let paged = lines_per_page.is_some();
+    if let Some(lines_per_page) = lines_per_page.filter(|_| new_items < old_items) {
+        let unloaded = remove_config_page_lines(
+            &tars.key(),
+            ctx.remaining_accounts,
+            lines_per_page,
+            new_items,
+            old_items,
+            line_size,
+        )?;
+        let mut data = tars_info.data.borrow_mut();
+        let loaded = get_config_count(&data)? - unloaded;
+        data[CONFIG_ARRAY_START..CONFIG_ARRAY_START + 4]
+            .copy_from_slice(&(loaded as u32).to_le_bytes());
+    }
+
+    if new_len > current_len {
+        let rent_exempt_balance = Rent::get()?.minimum_balance(new_len);
+        if tars_info.lamports() < rent_exempt_balance {
+            invoke(
+                &system_instruction::transfer(
+                    &ctx.accounts.payer.key(),
+                    tars_info.key,
+                    rent_exempt_balance - tars_info.lamports(),
+                ),
+                &[
+                    ctx.accounts.payer.to_account_info(),
+                    tars_info.clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+        tars_info.realloc(new_len, false)?;
+    }
+
+    if paged {
+        // New pages start out unused
+        let mut data = tars_info.data.borrow_mut();
+        let data_end = new_len.min(data.len());
+        if old_len < data_end {
+            data[old_len..data_end].fill(0);
+        }
+    } else {
+        let mut data = tars_info.data.borrow_mut();
+        let data_end = old_len.max(new_len).min(data.len());
+        relocate_config_bit_masks(&mut data, old_items, new_items, line_size, data_end);
+    }
+
+    if new_len < current_len {
+        tars_info.realloc(new_len, false)?;
+        let excess = tars_info
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(new_len));
+        **tars_info.try_borrow_mut_lamports()? -= excess;
+        let authority = ctx.accounts.authority.to_account_info();
+        **authority.try_borrow_mut_lamports()? = authority
+            .lamports()
+            .checked_add(excess)
+            .ok_or(TarsError::NumericalOverflowError)?;
+    }
+
+    tars.data.items_available = items_available;
+    Ok(())
+}
+
+// Moves the bitmasks of loaded and redeemed lines of a tars holding its config
+// lines from their offsets for `old_items` to their offsets for `new_items`,
+// clearing everything between the kept lines and `data_end`.
+fn relocate_config_bit_masks(
+    data: &mut [u8],
+    old_items: usize,
+    new_items: usize,
+    line_size: usize,
+    data_end: usize,
+) {
+    let old_bytes = old_items / 8 + 1;
+    let new_bytes = new_items / 8 + 1;
+    let old_bit_mask_start = bit_mask_start(old_items, line_size);
+    let old_redeemed_start = redeemed_start(old_items, line_size);
+    let mut bit_mask = data[old_bit_mask_start..old_bit_mask_start + old_bytes].to_vec();
+    let mut redeemed = data[old_redeemed_start..old_redeemed_start + old_bytes].to_vec();
+    bit_mask.resize(new_bytes, 0);
+    redeemed.resize(new_bytes, 0);
+
+    let lines_end = CONFIG_ARRAY_START + 4 + old_items.min(new_items) * line_size;
+    data[lines_end..data_end].fill(0);
+
+    if new_items < old_items {
+        // Forget lines past the new end so the loaded count stays right.
+        let remainder = new_items % 8;
+        bit_mask[new_items / 8] &= if remainder == 0 {
+            0
+        } else {
+            0xff << (8 - remainder)
+        };
+        let count: u32 = bit_mask.iter().map(|byte| byte.count_ones()).sum();
+        data[CONFIG_ARRAY_START..CONFIG_ARRAY_START + 4].copy_from_slice(&count.to_le_bytes());
+    }
+
+    let bit_mask_start = bit_mask_start(new_items, line_size);
+    let redeemed_start = redeemed_start(new_items, line_size);
+    data[bit_mask_start - 4..bit_mask_start]
+        .copy_from_slice(&((new_items / 8) as u32).to_le_bytes());
+    data[bit_mask_start..bit_mask_start + new_bytes].copy_from_slice(&bit_mask);
+    data[redeemed_start..redeemed_start + new_bytes].copy_from_slice(&redeemed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE_SIZE: usize = 2;
+
+    // In-account layout for `items` lines, with every line loaded and the
+    // given redeemed lines marked.
+    fn config_data(items: usize, redeemed_lines: &[usize], len: usize) -> Vec<u8> {
+        let mut data = vec![0u8; len];
+        data[CONFIG_ARRAY_START..CONFIG_ARRAY_START + 4]
+            .copy_from_slice(&(items as u32).to_le_bytes());
+        for position in 0..items {
+            let start = CONFIG_ARRAY_START + 4 + position * LINE_SIZE;
+            data[start..start + LINE_SIZE].fill(position as u8 + 1);
+        }
+        let bit_mask_start = bit_mask_start(items, LINE_SIZE);
+        let redeemed_start = redeemed_start(items, LINE_SIZE);
+        data[bit_mask_start - 4..bit_mask_start]
+            .copy_from_slice(&((items / 8) as u32).to_le_bytes());
+        for position in 0..items {
+            data[bit_mask_start + position / 8] |= 0x80 >> (position % 8);
+        }
+        for position in redeemed_lines {
+            data[redeemed_start + position / 8] |= 0x80 >> (position % 8);
+        }
+        data
+    }
+
+    fn config_layout_len(items: usize) -> usize {
+        redeemed_start(items, LINE_SIZE) + items / 8 + 1
+    }
+
+    #[test]
+    fn growing_keeps_lines_and_bit_masks() {
+        let old_items = 10;
+        let new_items = 20;
+        let len = config_layout_len(new_items);
+        let mut data = config_data(old_items, &[3, 9], config_layout_len(old_items));
+        let old_len = data.len();
+        data.resize(len, 0xaa);
+
+        relocate_config_bit_masks(&mut data, old_items, new_items, LINE_SIZE, len);
+
+        let bit_mask_start = bit_mask_start(new_items, LINE_SIZE);
+        let redeemed_start = redeemed_start(new_items, LINE_SIZE);
+        assert!(old_len < bit_mask_start);
+        assert_eq!(
+            &data[CONFIG_ARRAY_START..CONFIG_ARRAY_START + 4],
+            &10u32.to_le_bytes()
+        );
+        let line_9 = CONFIG_ARRAY_START + 4 + 9 * LINE_SIZE;
+        assert_eq!(&data[line_9..line_9 + LINE_SIZE], &[10, 10]);
+        // new lines start out empty, and so do the bits following the old ones
+        assert!(data[line_9 + LINE_SIZE..bit_mask_start - 4]
+            .iter()
+            .all(|byte| *byte == 0));
+        assert_eq!(
+            &data[bit_mask_start - 4..bit_mask_start],
+            &2u32.to_le_bytes()
+        );
+        assert_eq!(
+            &data[bit_mask_start..bit_mask_start + 3],
+            &[0xff, 0b1100_0000, 0]
+        );
+        assert_eq!(
+            &data[redeemed_start..redeemed_start + 3],
+            &[0b0001_0000, 0b0100_0000, 0]
+        );
+    }
+
+    #[test]
+    fn shrinking_forgets_lines_past_the_end() {
+        let old_items = 20;
+        let new_items = 11;
+        let len = config_layout_len(old_items);
+        let mut data = config_data(old_items, &[], len);
+
+        relocate_config_bit_masks(&mut data, old_items, new_items, LINE_SIZE, len);
+
+        let bit_mask_start = bit_mask_start(new_items, LINE_SIZE);
+        assert_eq!(
+            &data[CONFIG_ARRAY_START..CONFIG_ARRAY_START + 4],
+            &11u32.to_le_bytes()
+        );
+        assert_eq!(
+            &data[bit_mask_start - 4..bit_mask_start],
+            &1u32.to_le_bytes()
+        );
+        assert_eq!(
+            &data[bit_mask_start..bit_mask_start + 2],
+            &[0xff, 0b1110_0000]
+        );
+        // nothing of the old layout is left past the new one
+        assert!(data[config_layout_len(new_items)..]
+            .iter()
+            .all(|byte| *byte == 0));
+
+        // shrinking to a multiple of 8 drops the whole last byte
+        let mut data = config_data(old_items, &[], len);
+        relocate_config_bit_masks(&mut data, old_items, 16, LINE_SIZE, len);
+        let start = super::bit_mask_start(16, LINE_SIZE);
+        assert_eq!(
+            &data[CONFIG_ARRAY_START..CONFIG_ARRAY_START + 4],
+            &16u32.to_le_bytes()
+        );
+        assert_eq!(&data[start..start + 3], &[0xff, 0xff, 0]);
+    }
+}
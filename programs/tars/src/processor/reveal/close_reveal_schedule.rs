@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{REVEAL_FEATURE_INDEX, STAGED_REVEAL_FEATURE_INDEX, TARS_VERSION},
+    is_config_locked, RevealSchedule, Tars, TarsError,
+};
+
+/// Close the reveal schedule of a tars whose config is not locked yet, so the
+/// stages can be committed again.
+#[derive(Accounts)]
+pub struct CloseRevealSchedule<'info> {
+    #[account(mut, has_one = authority, constraint = tars.version == TARS_VERSION @ TarsError::TarsVersionMismatch)]
+    tars: Account<'info, Tars>,
+    #[account(mut)]
+    authority: Signer<'info>,
+    #[account(mut,
+        seeds = [b"reveal-schedule".as_ref(), tars.key().as_ref()],
+        bump,
+        close = authority
+    )]
+    reveal_schedule: Account<'info, RevealSchedule>,
+}
+
+pub fn handle_close_reveal_schedule(ctx: Context<CloseRevealSchedule>) -> Result<()> {
+    let tars = &mut ctx.accounts.tars;
+    if is_config_locked(tars) {
+        return err!(TarsError::ConfigLocked);
+    }
+
+    tars.remove_feature(STAGED_REVEAL_FEATURE_INDEX);
+    tars.remove_feature(REVEAL_FEATURE_INDEX);
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{REVEAL_FEATURE_INDEX, STAGED_REVEAL_FEATURE_INDEX, TARS_VERSION},
+    is_config_locked, Tars, TarsError,
+};
+
+/// Commit to the Merkle root of the config lines that NFTs of a hidden settings
+/// tars are revealed to, minted NFTs then stay updatable by the tars until revealed.
+#[derive(Accounts)]
+pub struct CommitReveal<'info> {
+    #[account(mut, has_one = authority, constraint = tars.version == TARS_VERSION @ TarsError::TarsVersionMismatch)]
+    tars: Account<'info, Tars>,
+    authority: Signer<'info>,
+}
+
+pub fn handle_commit_reveal(ctx: Context<CommitReveal>, root: [u8; 32]) -> Result<()> {
+    let tars = &mut ctx.accounts.tars;
+    if is_config_locked(tars) {
+        return err!(TarsError::ConfigLocked);
+    }
+    // Staged reveals take their roots from the reveal schedule only
+    if tars.is_feature_active(STAGED_REVEAL_FEATURE_INDEX) {
+        return err!(TarsError::RevealScheduleCommitted);
+    }
+    if !tars.data.retain_authority {
+        return err!(TarsError::RevealRequiresRetainAuthority);
+    }
+    match &mut tars.data.hidden_settings {
+        Some(hidden_settings) => hidden_settings.hash = root,
+        None => return err!(TarsError::RevealRequiresHiddenSettings),
+    }
+
+    tars.set_feature(REVEAL_FEATURE_INDEX);
+    Ok(())
+}
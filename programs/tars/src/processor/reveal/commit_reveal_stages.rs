@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{
+        MAX_REVEAL_STAGES, REVEAL_FEATURE_INDEX, STAGED_REVEAL_FEATURE_INDEX, TARS_VERSION,
+    },
+    is_config_locked, RevealSchedule, RevealStage, Tars, TarsError,
+};
+
+/// Commit to one Merkle root per reveal stage of a hidden settings tars, each
+/// stage unlocking at its own time. The stages must cover every item and can
+/// be committed again after `close_reveal_schedule` until the config is locked.
+#[derive(Accounts)]
+pub struct CommitRevealStages<'info> {
+    #[account(mut, has_one = authority, constraint = tars.version == TARS_VERSION @ TarsError::TarsVersionMismatch)]
+    tars: Account<'info, Tars>,
+    authority: Signer<'info>,
+    #[account(init,
+        payer = payer,
+        space = 8 + RevealSchedule::MAX_SERIALIZED_SIZE,
+        seeds = [b"reveal-schedule".as_ref(), tars.key().as_ref()],
+        bump
+    )]
+    reveal_schedule: Account<'info, RevealSchedule>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+pub fn handle_commit_reveal_stages(
+    ctx: Context<CommitRevealStages>,
+    stages: Vec<RevealStage>,
+) -> Result<()> {
+    let tars = &mut ctx.accounts.tars;
+    if is_config_locked(tars) {
+        return err!(TarsError::ConfigLocked);
+    }
+    if !tars.data.retain_authority {
+        return err!(TarsError::RevealRequiresRetainAuthority);
+    }
+    if tars.data.hidden_settings.is_none() {
+        return err!(TarsError::RevealRequiresHiddenSettings);
+    }
+    if stages.is_empty()
+        || stages.len() > MAX_REVEAL_STAGES
+        || stages.windows(2).any(|pair| {
+            pair[0].end_mint_number >= pair[1].end_mint_number
+                || pair[0].unlock_time > pair[1].unlock_time
+        })
+        || stages[stages.len() - 1].end_mint_number < tars.data.items_available
+    {
+        return err!(TarsError::InvalidRevealStages);
+    }
+
+    let reveal_schedule = &mut ctx.accounts.reveal_schedule;
+    reveal_schedule.tars = tars.key();
+    reveal_schedule.stages = stages;
+
+    tars.set_feature(REVEAL_FEATURE_INDEX);
+    tars.set_feature(STAGED_REVEAL_FEATURE_INDEX);
+    Ok(())
+}
@@ -0,0 +1,11 @@
+pub mod close_reveal_schedule;
+pub mod commit_reveal;
+pub mod commit_reveal_stages;
+pub mod reveal_nft;
+pub mod reveal_nfts;
+
+pub use close_reveal_schedule::*;
+pub use commit_reveal::*;
+pub use commit_reveal_stages::*;
+pub use reveal_nft::*;
+pub use reveal_nfts::*;
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{PREFIX, TARS_VERSION},
+    get_reveal_root, load_reveal_schedule, reveal_nft_metadata, ConfigLine, NftReveal,
+    RevealParams, Tars, TarsError,
+};
+
+/// Reveal an NFT of a hidden settings tars to its committed config line, anyone
+/// holding the proof can call it.
+#[derive(Accounts)]
+#[instruction(creator_bump: u8)]
+pub struct RevealNft<'info> {
+    #[account(constraint = tars.version == TARS_VERSION @ TarsError::TarsVersionMismatch)]
+    tars: Account<'info, Tars>,
+    /// CHECK: account constraints checked in account trait
+    #[account(seeds=[PREFIX.as_bytes(), tars.key().as_ref()], bump=creator_bump)]
+    tars_creator: UncheckedAccount<'info>,
+    /// CHECK: account checked in handler and CPI
+    #[account(mut, owner = mpl_token_metadata::id())]
+    metadata: UncheckedAccount<'info>,
+    /// CHECK: account checked in CPI
+    #[account(address = mpl_token_metadata::id())]
+    token_metadata_program: UncheckedAccount<'info>,
+    // > Only needed if tars has staged reveals
+    // reveal_schedule
+}
+
+pub fn handle_reveal_nft(
+    ctx: Context<RevealNft>,
+    creator_bump: u8,
+    mint_number: u64,
+    config_line: ConfigLine,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let tars = &ctx.accounts.tars;
+    let reveal_schedule = load_reveal_schedule(tars, ctx.remaining_accounts)?;
+    let root = get_reveal_root(
+        tars,
+        reveal_schedule.as_deref(),
+        mint_number,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    reveal_nft_metadata(RevealParams {
+        tars,
+        tars_creator: ctx.accounts.tars_creator.to_account_info(),
+        creator_bump,
+        metadata: ctx.accounts.metadata.to_account_info(),
+        token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+        root,
+        reveal: NftReveal {
+            mint_number,
+            config_line,
+            proof,
+        },
+    })
+}
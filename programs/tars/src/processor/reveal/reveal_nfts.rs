@@ -0,0 +1,379 @@
+use anchor_lang::prelude::*;
+use mpl_token_metadata::state::Metadata;
+
+use crate::{
+    cmp_pubkeys,
+    constants::{PREFIX, STAGED_REVEAL_FEATURE_INDEX, TARS_VERSION},
+    get_reveal_root, load_reveal_schedule, reveal_nft_metadata, MintReceipt, NftReveal,
+    RevealParams, Tars, TarsError,
+};
+
+/// Reveal a batch of NFTs of a hidden settings tars, cranked by anyone holding
+/// the proofs as the stages unlock. Every mint under a committed reveal writes
+/// a receipt of its mint number and metadata, the crank walks them in order and
+/// marks them revealed.
+#[derive(Accounts)]
+#[instruction(creator_bump: u8)]
+pub struct RevealNfts<'info> {
+    #[account(constraint = tars.version == TARS_VERSION @ TarsError::TarsVersionMismatch)]
+    tars: Account<'info, Tars>,
+    /// CHECK: account constraints checked in account trait
+    #[account(seeds=[PREFIX.as_bytes(), tars.key().as_ref()], bump=creator_bump)]
+    tars_creator: UncheckedAccount<'info>,
+    /// CHECK: account checked in CPI
+    #[account(address = mpl_token_metadata::id())]
+    token_metadata_program: UncheckedAccount<'info>,
+    // > Only needed if tars has staged reveals
+    // reveal_schedule
+    // > Repeated for every reveal, in order
+    // mint_receipt
+    // metadata
+}
+
+pub fn handle_reveal_nfts<'info>(
+    ctx: Context<'_, '_, '_, 'info, RevealNfts<'info>>,
+    creator_bump: u8,
+    reveals: Vec<NftReveal>,
+) -> Result<()> {
+    let tars = &ctx.accounts.tars;
+    let reveal_schedule = load_reveal_schedule(tars, ctx.remaining_accounts)?;
+    let mut remaining_accounts_counter: usize = 0;
+    if tars.is_feature_active(STAGED_REVEAL_FEATURE_INDEX) {
+        remaining_accounts_counter += 1;
+    }
+    if ctx.remaining_accounts.len() < remaining_accounts_counter + 2 * reveals.len() {
+        return err!(TarsError::MissingRevealMetadata);
+    }
+    let now = Clock::get()?.unix_timestamp;
+
+    for reveal in reveals {
+        let mint_receipt_info = &ctx.remaining_accounts[remaining_accounts_counter];
+        remaining_accounts_counter += 1;
+        let metadata = &ctx.remaining_accounts[remaining_accounts_counter];
+        remaining_accounts_counter += 1;
+
+        let mut mint_receipt: Account<MintReceipt> = Account::try_from(mint_receipt_info)?;
+        if !cmp_pubkeys(&mint_receipt.tars, &tars.key())
+            || mint_receipt.mint_number != reveal.mint_number
+            || !cmp_pubkeys(&mint_receipt.metadata, metadata.key)
+        {
+            return err!(TarsError::InvalidMintReceipt);
+        }
+        // Cranks overlap, and single reveals don't touch the receipt, so NFTs
+        // that were revealed already are skipped instead of failing the batch.
+        if mint_receipt.revealed {
+            continue;
+        }
+        if !cmp_pubkeys(
+            &Metadata::from_account_info(metadata)?.update_authority,
+            ctx.accounts.tars_creator.key,
+        ) {
+            mint_receipt.revealed = true;
+            mint_receipt.exit(&crate::id())?;
+            continue;
+        }
+
+        let mint_number = reveal.mint_number;
+        let root = get_reveal_root(tars, reveal_schedule.as_deref(), mint_number, now)?;
+        reveal_nft_metadata(RevealParams {
+            tars,
+            tars_creator: ctx.accounts.tars_creator.to_account_info(),
+            creator_bump,
+            metadata: metadata.clone(),
+            token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+            root,
+            reveal,
+        })?;
+        mint_receipt.revealed = true;
+        mint_receipt.exit(&crate::id())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        sync::{Mutex, Once},
+    };
+
+    use mpl_token_metadata::{
+        instruction::{MetadataInstruction, UpdateMetadataAccountArgsV2},
+        state::{Creator, Data, Key},
+    };
+    use solana_program::{
+        entrypoint::{ProgramResult, SUCCESS},
+        hash::hashv,
+        instruction::Instruction,
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+    };
+
+    use super::*;
+    use crate::{constants::REVEAL_FEATURE_INDEX, ConfigLine, HiddenSettings, TarsData};
+
+    const NOW: i64 = 1_700_000_000;
+
+    // Instructions the handler invoked, across every test of the module
+    static INVOKED: Mutex<Vec<Instruction>> = Mutex::new(Vec::new());
+
+    struct Stubs;
+
+    impl SyscallStubs for Stubs {
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            let clock = Clock {
+                unix_timestamp: NOW,
+                ..Clock::default()
+            };
+            unsafe { var_addr.cast::<Clock>().write(clock) };
+            SUCCESS
+        }
+
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            _account_infos: &[AccountInfo],
+            _signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            INVOKED.lock().unwrap().push(instruction.clone());
+            Ok(())
+        }
+    }
+
+    fn set_stubs() {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            set_syscall_stubs(Box::new(Stubs));
+        });
+    }
+
+    fn config_line() -> ConfigLine {
+        ConfigLine {
+            name: String::from("Tars Alpha"),
+            uri: String::from("https://arweave.net/alpha"),
+        }
+    }
+
+    fn reveal(mint_number: u64) -> NftReveal {
+        NftReveal {
+            mint_number,
+            config_line: config_line(),
+            proof: vec![],
+        }
+    }
+
+    // Accounts of the handler, in order: tars, tars_creator,
+    // token_metadata_program, then the remaining mint_receipt and metadata.
+    struct Fixture {
+        authority: Pubkey,
+        creator_bump: u8,
+        keys: [Pubkey; 5],
+        owners: [Pubkey; 5],
+        lamports: [u64; 5],
+        data: [Vec<u8>; 5],
+    }
+
+    impl Fixture {
+        /// Tars revealing mint number 0 to `config_line()`, with the receipt
+        /// and metadata of that NFT.
+        fn new() -> Self {
+            let authority = Pubkey::new_unique();
+            let tars_key = Pubkey::new_unique();
+            let (tars_creator, creator_bump) =
+                Pubkey::find_program_address(&[PREFIX.as_bytes(), tars_key.as_ref()], &crate::id());
+            let metadata_key = Pubkey::new_unique();
+
+            let mut tars = Tars {
+                version: TARS_VERSION,
+                authority,
+                data: TarsData {
+                    retain_authority: true,
+                    is_mutable: true,
+                    hidden_settings: Some(HiddenSettings {
+                        name: String::from("Tars "),
+                        uri: String::from("https://arweave.net/hidden"),
+                        // a single line is its own root
+                        hash: hashv(&[
+                            &[0],
+                            &0u64.to_le_bytes(),
+                            &config_line().try_to_vec().unwrap(),
+                        ])
+                        .to_bytes(),
+                    }),
+                    ..TarsData::default()
+                },
+                ..Tars::default()
+            };
+            tars.set_feature(REVEAL_FEATURE_INDEX);
+            let mut tars_data = Vec::new();
+            tars.try_serialize(&mut tars_data).unwrap();
+
+            let mut fixture = Fixture {
+                authority,
+                creator_bump,
+                keys: [
+                    tars_key,
+                    tars_creator,
+                    mpl_token_metadata::id(),
+                    Pubkey::new_unique(),
+                    metadata_key,
+                ],
+                owners: [
+                    crate::id(),
+                    System::id(),
+                    Pubkey::default(),
+                    crate::id(),
+                    mpl_token_metadata::id(),
+                ],
+                lamports: [1; 5],
+                data: [tars_data, vec![], vec![], vec![], vec![]],
+            };
+            fixture.set_receipt(0, false);
+            fixture.set_update_authority(tars_creator);
+            fixture
+        }
+
+        fn set_receipt(&mut self, mint_number: u64, revealed: bool) {
+            let mut data = Vec::new();
+            MintReceipt {
+                tars: self.keys[0],
+                mint_number,
+                metadata: self.keys[4],
+                revealed,
+            }
+            .try_serialize(&mut data)
+            .unwrap();
+            self.data[3] = data;
+        }
+
+        fn set_update_authority(&mut self, update_authority: Pubkey) {
+            self.data[4] = Metadata {
+                key: Key::MetadataV1,
+                update_authority,
+                mint: Pubkey::new_unique(),
+                data: Data {
+                    name: String::from("Tars #1"),
+                    symbol: String::from("TARS"),
+                    uri: String::from("https://arweave.net/hidden"),
+                    seller_fee_basis_points: 500,
+                    creators: Some(vec![Creator {
+                        address: self.keys[1],
+                        verified: true,
+                        share: 0,
+                    }]),
+                },
+                primary_sale_happened: true,
+                is_mutable: true,
+                edition_nonce: None,
+                token_standard: None,
+                collection: None,
+                uses: None,
+            }
+            .try_to_vec()
+            .unwrap();
+        }
+
+        fn reveal(&mut self, reveals: Vec<NftReveal>) -> Result<()> {
+            set_stubs();
+            let creator_bump = self.creator_bump;
+            let mut infos: Vec<AccountInfo> = self
+                .keys
+                .iter()
+                .zip(self.owners.iter())
+                .zip(self.lamports.iter_mut())
+                .zip(self.data.iter_mut())
+                .map(|(((key, owner), lamports), data)| {
+                    AccountInfo::new(key, false, true, lamports, data, owner, false, 0)
+                })
+                .collect();
+            let remaining_accounts = infos.split_off(3);
+            let mut accounts = RevealNfts {
+                tars: Account::try_from(&infos[0])?,
+                tars_creator: UncheckedAccount::try_from(infos[1].clone()),
+                token_metadata_program: UncheckedAccount::try_from(infos[2].clone()),
+            };
+            handle_reveal_nfts(
+                Context::new(
+                    &crate::ID,
+                    &mut accounts,
+                    &remaining_accounts,
+                    BTreeMap::new(),
+                ),
+                creator_bump,
+                reveals,
+            )
+        }
+
+        fn receipt(&self) -> MintReceipt {
+            MintReceipt::try_deserialize(&mut &self.data[3][..]).unwrap()
+        }
+
+        fn metadata_updates(&self) -> Vec<UpdateMetadataAccountArgsV2> {
+            INVOKED
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|instruction| instruction.accounts[0].pubkey == self.keys[4])
+                .map(|instruction| {
+                    match MetadataInstruction::try_from_slice(&instruction.data).unwrap() {
+                        MetadataInstruction::UpdateMetadataAccountV2(args) => args,
+                        _ => panic!("unexpected metadata instruction"),
+                    }
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn reveals_nfts_of_their_mint_receipts() {
+        let mut fixture = Fixture::new();
+        fixture.reveal(vec![reveal(0)]).unwrap();
+
+        assert!(fixture.receipt().revealed);
+        let updates = fixture.metadata_updates();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].update_authority, Some(fixture.authority));
+        let data = updates[0].data.as_ref().unwrap();
+        assert_eq!(data.name, config_line().name);
+        assert_eq!(data.uri, config_line().uri);
+        assert_eq!(data.symbol, "TARS");
+
+        // cranking the receipt again skips it
+        fixture.reveal(vec![reveal(0)]).unwrap();
+        assert_eq!(fixture.metadata_updates().len(), 1);
+    }
+
+    #[test]
+    fn marks_receipts_of_nfts_revealed_on_their_own() {
+        let mut fixture = Fixture::new();
+        let authority = fixture.authority;
+        fixture.set_update_authority(authority);
+
+        fixture.reveal(vec![reveal(0)]).unwrap();
+        assert!(fixture.receipt().revealed);
+        assert!(fixture.metadata_updates().is_empty());
+    }
+
+    #[test]
+    fn rejects_receipts_of_other_mints() {
+        let mut fixture = Fixture::new();
+        fixture.set_receipt(1, false);
+
+        let error: ProgramError = fixture.reveal(vec![reveal(0)]).unwrap_err().into();
+        assert_eq!(
+            error,
+            ProgramError::Custom(TarsError::InvalidMintReceipt.into())
+        );
+        assert!(!fixture.receipt().revealed);
+        assert!(fixture.metadata_updates().is_empty());
+
+        // nor reveals without their receipt
+        let error: ProgramError = fixture
+            .reveal(vec![reveal(0), reveal(1)])
+            .unwrap_err()
+            .into();
+        assert_eq!(
+            error,
+            ProgramError::Custom(TarsError::MissingRevealMetadata.into())
+        );
+    }
+}
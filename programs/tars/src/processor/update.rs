@@ -1,8 +1,12 @@
 use anchor_lang::prelude::*;
 
 use crate::{
-    constants::COLLECTIONS_FEATURE_INDEX, is_feature_active, TarsError, Tars,
-    TarsData,
+    assert_valid_burn_to_mint, assert_valid_config_line_template, assert_valid_config_pages,
+    assert_valid_gatekeeper, assert_valid_holder_discounts, config_line_size,
+    constants::{
+        COLLECTIONS_FEATURE_INDEX, REVEAL_FEATURE_INDEX, STAGED_REVEAL_FEATURE_INDEX, TARS_VERSION,
+    },
+    is_config_locked, is_feature_active, Tars, TarsData, TarsError,
 };
 
 /// Update the tars state.
@@ -10,7 +14,8 @@ use crate::{
 pub struct UpdateTars<'info> {
     #[account(
     mut,
-    has_one = authority
+    has_one = authority,
+    constraint = tars.version == TARS_VERSION @ TarsError::TarsVersionMismatch
     )]
     tars: Account<'info, Tars>,
     authority: Signer<'info>,
@@ -50,11 +55,49 @@ pub fn handle_update_tars(
         return err!(TarsError::CannotSwitchToHiddenSettings);
     }
 
+    // Loaded lines keep their layout, only the template prefixes can change.
+    if data.config_line_template.is_some() != tars.data.config_line_template.is_some()
+        || config_line_size(&data) != config_line_size(&tars.data)
+    {
+        return err!(TarsError::CannotChangeConfigLineSize);
+    }
+    if is_config_locked(tars) && data.config_line_template != tars.data.config_line_template {
+        return err!(TarsError::ConfigLocked);
+    }
+    // Config pages are laid out for their number of lines
+    if data.config_pages != tars.data.config_pages {
+        return err!(TarsError::CannotChangeConfigPages);
+    }
+
+    if tars.is_feature_active(REVEAL_FEATURE_INDEX) {
+        if data.hidden_settings.is_none() {
+            return err!(TarsError::RevealRequiresHiddenSettings);
+        }
+        if !data.retain_authority {
+            return err!(TarsError::RevealRequiresRetainAuthority);
+        }
+        // The committed root can't change once minting started
+        if is_config_locked(tars) && data.hidden_settings != tars.data.hidden_settings {
+            return err!(TarsError::ConfigLocked);
+        }
+        // The reveal stages must keep covering every item
+        if tars.is_feature_active(STAGED_REVEAL_FEATURE_INDEX)
+            && data.items_available != tars.data.items_available
+        {
+            return err!(TarsError::InvalidRevealStages);
+        }
+    }
+
     let old_uuid = tars.data.uuid.clone();
     tars.wallet = ctx.accounts.wallet.key();
     if is_feature_active(&old_uuid, COLLECTIONS_FEATURE_INDEX) && !data.retain_authority {
         return err!(TarsError::TarsCollectionRequiresRetainAuthority);
     }
+    assert_valid_gatekeeper(&data.gatekeeper)?;
+    assert_valid_burn_to_mint(&data.burn_to_mint)?;
+    assert_valid_holder_discounts(&data.holder_discounts)?;
+    assert_valid_config_line_template(&data.config_line_template)?;
+    assert_valid_config_pages(&data)?;
     tars.data = data;
     tars.data.uuid = old_uuid;
 
@@ -1,20 +1,57 @@
 use anchor_lang::prelude::*;
+use mpl_token_metadata::state::{
+    MAX_CREATOR_LIMIT, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH,
+};
+use proc_macros::MaxSerializedSize;
+
+use crate::{
+    constants::{MAX_GATEKEEPER_NETWORKS, MAX_HOLDER_DISCOUNTS, MAX_REVEAL_STAGES, UUID_LENGTH},
+    TarsError,
+};
 
 /// Tars state and config data.
+///
+/// Changing the size moves `CONFIG_ARRAY_START`, existing accounts then need a
+/// new `TARS_VERSION` and migration.
+#[proc_macros::assert_size(borsh = 1366)]
 #[account]
-#[derive(Default, Debug)]
+#[derive(Default, Debug, MaxSerializedSize)]
 pub struct Tars {
+    /// Layout version of the account, see `TARS_VERSION`
+    pub version: u8,
     pub authority: Pubkey,
     pub wallet: Pubkey,
     pub token_mint: Option<Pubkey>,
     pub items_redeemed: u64,
     pub data: TarsData,
+    /// Slot of the last mint, used by `max_mints_per_slot`
+    pub last_mint_slot: u64,
+    /// Number of mints in `last_mint_slot`
+    pub mints_in_slot: u16,
+    /// Total amount paid for mints, in lamports or tars tokens
+    pub proceeds: u64,
+    /// Bitfield of the features in use, indexed by the `*_FEATURE_INDEX` constants
+    pub features: u32,
     // there's a borsh vec u32 denoting how many actual lines of data there are currently (eventually equals items available)
     // There is actually lines and lines of data after this but we explicitly never want them deserialized.
     // here there is a borsh vec u32 indicating number of bytes in bitmask array.
     // here there is a number of bytes equal to ceil(max_number_of_lines/8) and it is a bit mask used to figure out when to increment borsh vec u32
 }
 
+impl Tars {
+    pub fn is_feature_active(&self, feature_index: usize) -> bool {
+        self.features & (1 << feature_index) != 0
+    }
+
+    pub fn set_feature(&mut self, feature_index: usize) {
+        self.features |= 1 << feature_index;
+    }
+
+    pub fn remove_feature(&mut self, feature_index: usize) {
+        self.features &= !(1 << feature_index);
+    }
+}
+
 /// Collection PDA account
 #[account]
 #[derive(Default, Debug)]
@@ -23,12 +60,129 @@ pub struct CollectionPDA {
     pub tars: Pubkey,
 }
 
+/// Tracks how many times an NFT has been used towards mints of a tars.
+#[account]
+#[derive(Default, Debug, MaxSerializedSize)]
+pub struct NftUsage {
+    pub tars: Pubkey,
+    pub nft_mint: Pubkey,
+    pub uses: u16,
+}
+
+/// Sorted wallets that can't mint from a tars.
+#[account]
+#[derive(Default, Debug, MaxSerializedSize)]
+pub struct Blocklist {
+    pub tars: Pubkey,
+    pub max_wallets: u32,
+    /// Room for `max_wallets` is added when the blocklist is created
+    #[max_len(0)]
+    pub wallets: Vec<Pubkey>,
+}
+
+impl Blocklist {
+    pub fn is_blocked(&self, wallet: &Pubkey) -> bool {
+        self.wallets.binary_search(wallet).is_ok()
+    }
+}
+
+/// Tracks when a wallet last minted from a tars.
+#[account]
+#[derive(Default, Debug, MaxSerializedSize)]
+pub struct WalletMintRecord {
+    pub tars: Pubkey,
+    pub wallet: Pubkey,
+    pub last_mint_time: i64,
+}
+
+/// Tracks how many times a mint voucher has been used.
+#[account]
+#[derive(Default, Debug, MaxSerializedSize)]
+pub struct MintVoucherRecord {
+    pub tars: Pubkey,
+    pub buyer: Pubkey,
+    pub nonce: u64,
+    pub uses: u16,
+}
+
+/// Written for every NFT minted while a reveal is committed, the reveal crank
+/// walks them by mint number.
+#[account]
+#[derive(Default, Debug, MaxSerializedSize)]
+pub struct MintReceipt {
+    pub tars: Pubkey,
+    pub mint_number: u64,
+    pub metadata: Pubkey,
+    pub revealed: bool,
+}
+
+/// Reveal stages of a hidden settings tars, replacing its single committed root.
+#[account]
+#[derive(Default, Debug, MaxSerializedSize)]
+pub struct RevealSchedule {
+    pub tars: Pubkey,
+    /// Ordered by `end_mint_number`, at most `MAX_REVEAL_STAGES`
+    #[max_len(MAX_REVEAL_STAGES)]
+    pub stages: Vec<RevealStage>,
+}
+
+impl RevealSchedule {
+    pub fn stage(&self, mint_number: u64) -> Option<&RevealStage> {
+        self.stages
+            .iter()
+            .find(|stage| mint_number < stage.end_mint_number)
+    }
+}
+
+/// Mints numbered from the end of the previous stage up to `end_mint_number`,
+/// revealed against `root` from `unlock_time`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, MaxSerializedSize)]
+pub struct RevealStage {
+    pub end_mint_number: u64,
+    /// Merkle root of the revealed config lines, see [`crate::verify_reveal_proof`]
+    pub root: [u8; 32],
+    pub unlock_time: i64,
+}
+
+/// Config line an NFT is revealed to, with its proof.
+#[derive(AnchorSerialize, AnchorDeserialize, Debug)]
+pub struct NftReveal {
+    pub mint_number: u64,
+    pub config_line: ConfigLine,
+    pub proof: Vec<[u8; 32]>,
+}
+
+/// Hash chain over the config lines of a locked tars.
+#[account]
+#[derive(Default, Debug, MaxSerializedSize)]
+pub struct ConfigManifest {
+    pub tars: Pubkey,
+    /// Number of config lines hashed so far, in index order
+    pub lines_hashed: u64,
+    /// Starts as the hash of the config line template, then `hash(hash, line)`
+    /// for every stored line
+    pub hash: [u8; 32],
+}
+
+/// Holds `lines_per_page` config lines of a tars using config pages.
+#[account]
+#[derive(Default, Debug, MaxSerializedSize)]
+pub struct ConfigPage {
+    pub tars: Pubkey,
+    /// Position of the page, it holds the lines from `index * lines_per_page`
+    pub index: u32,
+    // The bitmask of the lines that were added and the one of the lines that
+    // were minted follow, then the config lines, they are never deserialized.
+}
+
 /// Tars settings data.
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, MaxSerializedSize)]
 pub struct TarsData {
+    #[max_len(UUID_LENGTH)]
     pub uuid: String,
     pub price: u64,
     /// The symbol for the asset
+    #[max_len(MAX_SYMBOL_LENGTH)]
     pub symbol: String,
     /// Royalty basis points that goes to creators in secondary sales (0-10000)
     pub seller_fee_basis_points: u16,
@@ -37,12 +191,35 @@ pub struct TarsData {
     pub retain_authority: bool,
     pub go_live_date: Option<i64>,
     pub end_settings: Option<EndSettings>,
+    #[max_len(MAX_CREATOR_LIMIT)]
     pub creators: Vec<Creator>,
     pub hidden_settings: Option<HiddenSettings>,
     pub whitelist_mint_settings: Option<WhitelistMintSettings>,
     pub items_available: u64,
     /// If [`Some`] requires gateway tokens on mint
-    pub gatekeeper: Option<GatekeeperConfig>,
+    pub gatekeeper: Option<GatekeeperSettings>,
+    /// If [`Some`] requires holding an NFT of a verified collection on mint
+    pub collection_gate: Option<CollectionGateSettings>,
+    /// If [`Some`] requires burning NFTs of a verified collection on mint
+    pub burn_to_mint: Option<BurnToMintSettings>,
+    /// Discounts for holders of verified collections, at most `MAX_HOLDER_DISCOUNTS`
+    #[max_len(MAX_HOLDER_DISCOUNTS)]
+    pub holder_discounts: Vec<HolderDiscount>,
+    /// If [`Some`] every mint needs a [`MintVoucher`] signed by this key
+    pub authorized_signer: Option<Pubkey>,
+    /// If [`Some`] limits how fast mints can happen
+    pub rate_limit: Option<RateLimitSettings>,
+    /// If [`Some`] config lines only store the suffixes of their name and uri
+    pub config_line_template: Option<ConfigLineTemplate>,
+    /// If [`Some`] config lines live in config pages instead of the tars account
+    pub config_pages: Option<ConfigPageSettings>,
+}
+
+/// Settings of a tars keeping its config lines in config pages.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug, MaxSerializedSize)]
+pub struct ConfigPageSettings {
+    /// Number of config lines in every config page
+    pub lines_per_page: u32,
 }
 
 /// Individual config line for storing NFT data pre-mint.
@@ -53,20 +230,32 @@ pub struct ConfigLine {
     pub uri: String,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub struct EndSettings {
-    pub end_setting_type: EndSettingType,
-    pub number: u64,
+/// Parts shared by the config lines of a tars, they are stored without them.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug, MaxSerializedSize)]
+pub struct ConfigLineTemplate {
+    #[max_len(MAX_NAME_LENGTH)]
+    pub name_prefix: String,
+    #[max_len(MAX_URI_LENGTH)]
+    pub uri_prefix: String,
+    /// Bytes stored for the name suffix of every config line
+    pub name_suffix_length: u8,
+    /// Bytes stored for the uri suffix of every config line
+    pub uri_suffix_length: u8,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub enum EndSettingType {
-    Date,
-    Amount,
+/// Sale end conditions, the sale ends as soon as any of them is hit.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, MaxSerializedSize)]
+pub struct EndSettings {
+    /// Unix timestamp after which only the authority can mint
+    pub end_date: Option<i64>,
+    /// Number of items after which the sale ends
+    pub max_redeemed: Option<u64>,
+    /// Total proceeds, in lamports or tars tokens, after which the sale ends
+    pub max_proceeds: Option<u64>,
 }
 
 // Unfortunate duplication of token metadata so that IDL picks it up.
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, MaxSerializedSize)]
 pub struct Creator {
     pub address: Pubkey,
     pub verified: bool,
@@ -75,14 +264,20 @@ pub struct Creator {
 }
 
 /// Hidden Settings for large mints used with offline data.
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Default, PartialEq, Debug, MaxSerializedSize,
+)]
 pub struct HiddenSettings {
+    #[max_len(MAX_NAME_LENGTH)]
     pub name: String,
+    #[max_len(MAX_URI_LENGTH)]
     pub uri: String,
+    /// Merkle root of the revealed config lines once committed with
+    /// `commit_reveal`, see [`crate::verify_reveal_proof`]
     pub hash: [u8; 32],
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, MaxSerializedSize)]
 pub struct WhitelistMintSettings {
     pub mode: WhitelistMintMode,
     pub mint: Pubkey,
@@ -90,7 +285,7 @@ pub struct WhitelistMintSettings {
     pub discount_price: Option<u64>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug, MaxSerializedSize)]
 pub enum WhitelistMintMode {
     // Only captcha uses the bytes, the others just need to have same length
     // for front end borsh to not crap itself
@@ -99,8 +294,24 @@ pub enum WhitelistMintMode {
     NeverBurn,
 }
 
-/// Configurations options for the gatekeeper.
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+/// Gatekeeper networks to verify on mint, at most `MAX_GATEKEEPER_NETWORKS`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, MaxSerializedSize)]
+pub struct GatekeeperSettings {
+    #[max_len(MAX_GATEKEEPER_NETWORKS)]
+    pub networks: Vec<GatekeeperConfig>,
+    pub policy: GatekeeperPolicy,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug, MaxSerializedSize)]
+pub enum GatekeeperPolicy {
+    /// A valid gateway token of any one network is enough
+    Any,
+    /// Valid gateway tokens of all networks are needed
+    All,
+}
+
+/// Configurations options for a gatekeeper network.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, MaxSerializedSize)]
 pub struct GatekeeperConfig {
     /// The network for the gateway token required
     pub gatekeeper_network: Pubkey,
@@ -108,3 +319,81 @@ pub struct GatekeeperConfig {
     /// The gatekeeper network must support this if true.
     pub expire_on_use: bool,
 }
+
+/// Configuration options for the collection gate.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, MaxSerializedSize)]
+pub struct CollectionGateSettings {
+    /// Mint of the collection NFT that gating NFTs must be verified against
+    pub collection: Pubkey,
+    /// How many mints each gating NFT allows, [`None`] for unlimited
+    pub uses_per_nft: Option<u16>,
+}
+
+/// Configuration options for burn-to-mint.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, MaxSerializedSize)]
+pub struct BurnToMintSettings {
+    /// Mint of the collection NFT that burned NFTs must be verified against
+    pub collection: Pubkey,
+    /// Number of NFTs to burn for every mint
+    pub burn_count: u8,
+    /// If [`Some`] replaces the tars price for burn-to-mint mints
+    pub price: Option<u64>,
+}
+
+/// Discount for holders of an NFT of a verified collection.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, MaxSerializedSize)]
+pub struct HolderDiscount {
+    /// Mint of the collection NFT that held NFTs must be verified against
+    pub collection: Pubkey,
+    pub discount: HolderDiscountType,
+    /// How many discounted mints each NFT allows, [`None`] for unlimited
+    pub uses_per_nft: Option<u16>,
+}
+
+impl HolderDiscount {
+    /// Price a holder pays instead of `price`.
+    pub fn discount_price(&self, price: u64) -> Result<u64> {
+        match self.discount {
+            HolderDiscountType::Price(discount_price) => Ok(discount_price),
+            HolderDiscountType::Percentage(percentage) => {
+                let discount = (price as u128)
+                    .checked_mul(percentage as u128)
+                    .ok_or(TarsError::NumericalOverflowError)?
+                    / 100;
+                Ok(price - discount as u64)
+            }
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, MaxSerializedSize)]
+pub enum HolderDiscountType {
+    /// Holders pay this price
+    Price(u64),
+    /// Holders get this many percent off the price
+    Percentage(u8),
+}
+
+/// Off-chain mint authorization, signed by the tars' `authorized_signer` in an
+/// Ed25519 program instruction of the mint transaction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct MintVoucher {
+    pub tars: Pubkey,
+    pub buyer: Pubkey,
+    /// How many mints the voucher allows
+    pub max_quantity: u16,
+    /// Price charged for mints with the voucher
+    pub price: u64,
+    /// Unix timestamp after which the voucher can't be used
+    pub expiry: i64,
+    pub nonce: u64,
+}
+
+/// Configuration options for mint rate limits.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, MaxSerializedSize)]
+pub struct RateLimitSettings {
+    /// Minimum number of seconds between mints by the same wallet
+    pub wallet_cooldown: Option<i64>,
+    /// Maximum number of mints in a single slot across the tars
+    pub max_mints_per_slot: Option<u16>,
+}
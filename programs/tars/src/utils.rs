@@ -1,18 +1,42 @@
 use std::str::from_utf8_unchecked;
 
 use anchor_lang::prelude::*;
+use arrayref::array_ref;
+use mpl_token_metadata::{
+    instruction::update_metadata_accounts_v2,
+    state::{DataV2, Metadata, MAX_NAME_LENGTH, MAX_URI_LENGTH},
+    utils::{assert_derivation, create_or_allocate_account_raw},
+};
+use solana_gateway::{
+    state::{GatewayTokenAccess, InPlaceGatewayToken},
+    Gateway,
+};
 use solana_program::{
     account_info::AccountInfo,
     clock::Clock,
+    ed25519_program,
+    entrypoint::MAX_PERMITTED_DATA_INCREASE,
+    hash::hashv,
     program::{invoke, invoke_signed},
     program_memory::sol_memcmp,
     program_pack::{IsInitialized, Pack},
     pubkey::{Pubkey, PUBKEY_BYTES},
     system_instruction,
+    sysvar::instructions::load_instruction_at_checked,
 };
 use spl_associated_token_account::get_associated_token_address;
 
-use crate::{TarsError, Tars};
+use crate::{
+    constants::{
+        CONFIG_LINE_SIZE, CONFIG_LOCKED_FEATURE_INDEX, CONFIG_PAGE_CURSOR_START,
+        CONFIG_PAGE_MASKS_START, EXPIRE_OFFSET, MAX_GATEKEEPER_NETWORKS, MAX_HOLDER_DISCOUNTS,
+        PREFIX, REVEAL_FEATURE_INDEX, STAGED_REVEAL_FEATURE_INDEX, USED_CONFIG_PAGES_START,
+    },
+    get_config_count, Blocklist, BurnToMintSettings, ConfigLine, ConfigLineTemplate, ConfigPage,
+    EndSettings, GatekeeperPolicy, GatekeeperSettings, HolderDiscount, HolderDiscountType,
+    MintReceipt, MintVoucher, MintVoucherRecord, NftReveal, NftUsage, RevealSchedule, Tars,
+    TarsData, TarsError, WalletMintRecord, WhitelistMintSettings,
+};
 
 pub fn assert_initialized<T: Pack + IsInitialized>(account_info: &AccountInfo) -> Result<T> {
     let account: T = T::unpack_unchecked(&account_info.data.borrow())?;
@@ -48,6 +72,32 @@ pub fn assert_valid_go_live<'info>(
     Ok(())
 }
 
+/// Checks that none of the end conditions of the sale has been hit. The
+/// authority can still mint past the end date.
+pub fn assert_sale_not_ended(
+    end_settings: &EndSettings,
+    tars: &Tars,
+    payer: &Pubkey,
+    now: i64,
+) -> Result<()> {
+    if let Some(end_date) = end_settings.end_date {
+        if now > end_date && !cmp_pubkeys(payer, &tars.authority) {
+            return err!(TarsError::SaleEndedByDate);
+        }
+    }
+    if let Some(max_redeemed) = end_settings.max_redeemed {
+        if tars.items_redeemed >= max_redeemed {
+            return err!(TarsError::SaleEndedByAmount);
+        }
+    }
+    if let Some(max_proceeds) = end_settings.max_proceeds {
+        if tars.proceeds >= max_proceeds {
+            return err!(TarsError::SaleEndedByProceeds);
+        }
+    }
+    Ok(())
+}
+
 pub fn assert_owned_by(account: &AccountInfo, owner: &Pubkey) -> Result<()> {
     if !cmp_pubkeys(account.owner, owner) {
         Err(TarsError::IncorrectOwner.into())
@@ -176,6 +226,1118 @@ pub fn spl_token_burn(params: TokenBurnParams<'_, '_>) -> Result<()> {
     result.map_err(|_| TarsError::TokenBurnFailed.into())
 }
 
+/// TokenMintToParams
+pub struct TokenMintToParams<'a: 'b, 'b> {
+    /// mint
+    /// CHECK: account checked in CPI
+    pub mint: AccountInfo<'a>,
+    /// destination
+    /// CHECK: account checked in CPI
+    pub destination: AccountInfo<'a>,
+    /// amount
+    pub amount: u64,
+    /// authority
+    /// CHECK: account checked in CPI
+    pub authority: AccountInfo<'a>,
+    /// authority_signer_seeds
+    pub authority_signer_seeds: &'b [&'b [u8]],
+    /// token_program
+    /// CHECK: account checked in CPI
+    pub token_program: AccountInfo<'a>,
+}
+
+pub fn spl_token_mint_to(params: TokenMintToParams<'_, '_>) -> Result<()> {
+    let TokenMintToParams {
+        mint,
+        destination,
+        authority,
+        token_program,
+        amount,
+        authority_signer_seeds,
+    } = params;
+    let result = invoke_signed(
+        &spl_token::instruction::mint_to(
+            token_program.key,
+            mint.key,
+            destination.key,
+            authority.key,
+            &[],
+            amount,
+        )?,
+        &[mint, destination, authority, token_program],
+        &[authority_signer_seeds],
+    );
+    result.map_err(|_| TarsError::TokenMintToFailed.into())
+}
+
+/// Checks that `token_account` holds an NFT of `owner` whose metadata is a
+/// verified member of `collection`, and returns the NFT's metadata.
+pub fn assert_collection_nft(
+    token_account_info: &AccountInfo,
+    metadata_info: &AccountInfo,
+    owner: &Pubkey,
+    collection: &Pubkey,
+) -> Result<Metadata> {
+    assert_owned_by(token_account_info, &spl_token::id())?;
+    let token_account: spl_token::state::Account = assert_initialized(token_account_info)?;
+    assert_keys_equal(&token_account.owner, owner)?;
+    if token_account.amount < 1 {
+        return err!(TarsError::MissingCollectionNft);
+    }
+
+    assert_owned_by(metadata_info, &mpl_token_metadata::id())?;
+    assert_derivation(
+        &mpl_token_metadata::id(),
+        metadata_info,
+        &[
+            mpl_token_metadata::state::PREFIX.as_bytes(),
+            mpl_token_metadata::id().as_ref(),
+            token_account.mint.as_ref(),
+        ],
+    )?;
+    let metadata = Metadata::from_account_info(metadata_info)?;
+    match &metadata.collection {
+        Some(c) if c.verified && cmp_pubkeys(&c.key, collection) => Ok(metadata),
+        _ => err!(TarsError::InvalidCollectionNft),
+    }
+}
+
+/// NftUsageParams
+pub struct NftUsageParams<'a: 'b, 'b> {
+    /// usage
+    /// CHECK: derivation checked in handler
+    pub usage: AccountInfo<'a>,
+    /// seed prefix telling apart what the NFT was used for
+    pub prefix: &'b [u8],
+    /// tars
+    pub tars: Pubkey,
+    /// nft_mint
+    pub nft_mint: Pubkey,
+    /// max_uses
+    pub max_uses: u16,
+    /// payer
+    /// CHECK: account checked in CPI
+    pub payer: AccountInfo<'a>,
+    /// system_program
+    /// CHECK: account checked in CPI
+    pub system_program: AccountInfo<'a>,
+    /// rent
+    /// CHECK: account checked in CPI
+    pub rent: AccountInfo<'a>,
+}
+
+/// Counts one use of an NFT in its usage PDA, creating the PDA on first use.
+pub fn record_nft_usage(params: NftUsageParams<'_, '_>) -> Result<()> {
+    let NftUsageParams {
+        usage,
+        prefix,
+        tars,
+        nft_mint,
+        max_uses,
+        payer,
+        system_program,
+        rent,
+    } = params;
+
+    let seeds = [prefix, tars.as_ref(), nft_mint.as_ref()];
+    let (usage_key, bump) = Pubkey::find_program_address(&seeds, &crate::id());
+    assert_keys_equal(&usage_key, usage.key)?;
+
+    if usage.data_is_empty() {
+        create_or_allocate_account_raw(
+            crate::id(),
+            &usage,
+            &rent,
+            &system_program,
+            &payer,
+            8 + NftUsage::MAX_SERIALIZED_SIZE,
+            &[prefix, tars.as_ref(), nft_mint.as_ref(), &[bump]],
+        )?;
+        let mut data_ref: &mut [u8] = &mut usage.try_borrow_mut_data()?;
+        NftUsage {
+            tars,
+            nft_mint,
+            uses: 0,
+        }
+        .try_serialize(&mut data_ref)?;
+    }
+
+    let mut nft_usage: Account<NftUsage> = Account::try_from(&usage)?;
+    if nft_usage.uses >= max_uses {
+        return err!(TarsError::NftUsesExhausted);
+    }
+    nft_usage.uses = nft_usage
+        .uses
+        .checked_add(1)
+        .ok_or(TarsError::NumericalOverflowError)?;
+    nft_usage.exit(&crate::id())?;
+    Ok(())
+}
+
+pub fn assert_valid_gatekeeper(gatekeeper: &Option<GatekeeperSettings>) -> Result<()> {
+    if let Some(gatekeeper) = gatekeeper {
+        if gatekeeper.networks.is_empty() || gatekeeper.networks.len() > MAX_GATEKEEPER_NETWORKS {
+            return err!(TarsError::InvalidGatekeeperSettings);
+        }
+    }
+    Ok(())
+}
+
+pub fn assert_valid_burn_to_mint(burn_to_mint: &Option<BurnToMintSettings>) -> Result<()> {
+    if let Some(burn_to_mint) = burn_to_mint {
+        if burn_to_mint.burn_count == 0 {
+            return err!(TarsError::InvalidBurnToMintSettings);
+        }
+    }
+    Ok(())
+}
+
+/// Verifies the gateway tokens of `wallet` for the gatekeeper networks of the
+/// tars according to the gatekeeper policy. The accounts of every network are
+/// read from `remaining_accounts` at `counter`, in the order of the networks.
+pub fn verify_gatekeepers<'a>(
+    data: &TarsData,
+    gatekeeper: &GatekeeperSettings,
+    remaining_accounts: &[AccountInfo<'a>],
+    counter: &mut usize,
+    wallet: &AccountInfo<'a>,
+) -> Result<()> {
+    // Eval function used in the gateway CPI
+    let eval_function = |token: &InPlaceGatewayToken<&[u8]>| match (data, token.expire_time()) {
+        (
+            TarsData {
+                go_live_date: Some(go_live_date),
+                whitelist_mint_settings: Some(WhitelistMintSettings { presale, .. }),
+                ..
+            },
+            Some(expire_time),
+        ) if !*presale && expire_time < go_live_date + EXPIRE_OFFSET => {
+            msg!(
+                "Invalid gateway token: calculated creation time {} and go_live_date {}",
+                expire_time - EXPIRE_OFFSET,
+                go_live_date
+            );
+            Err(error!(TarsError::GatewayTokenExpireTimeInvalid).into())
+        }
+        _ => Ok(()),
+    };
+
+    let mut passed = false;
+    for network in &gatekeeper.networks {
+        if remaining_accounts.len() <= *counter {
+            return Err(error!(TarsError::TarsNotLiveForWL));
+        }
+        let gateway_token_info = &remaining_accounts[*counter];
+        *counter += 1;
+
+        let expire_accounts = if network.expire_on_use {
+            if remaining_accounts.len() <= *counter {
+                return err!(TarsError::GatewayAppMissing);
+            }
+            let gateway_app = &remaining_accounts[*counter];
+            *counter += 1;
+
+            if remaining_accounts.len() <= *counter {
+                return err!(TarsError::NetworkExpireFeatureMissing);
+            }
+            let network_expire_feature = &remaining_accounts[*counter];
+            *counter += 1;
+            Some((gateway_app, network_expire_feature))
+        } else {
+            None
+        };
+
+        // Once a token passed under the Any policy the accounts of the remaining
+        // networks are only skipped over, so no other token gets expired.
+        if passed && gatekeeper.policy == GatekeeperPolicy::Any {
+            continue;
+        }
+
+        let result = match expire_accounts {
+            Some((gateway_app, network_expire_feature)) => {
+                Gateway::verify_and_expire_token_with_eval(
+                    gateway_app.clone(),
+                    gateway_token_info.clone(),
+                    wallet.clone(),
+                    &network.gatekeeper_network,
+                    network_expire_feature.clone(),
+                    eval_function,
+                )
+            }
+            None => Gateway::verify_gateway_token_with_eval(
+                gateway_token_info,
+                wallet.key,
+                &network.gatekeeper_network,
+                None,
+                eval_function,
+            ),
+        };
+        match result {
+            Ok(_) => passed = true,
+            Err(_) if gatekeeper.policy == GatekeeperPolicy::Any => {}
+            Err(_) => return Err(error!(TarsError::TarsNotLiveForWL)),
+        }
+    }
+
+    if !passed {
+        return Err(error!(TarsError::TarsNotLiveForWL));
+    }
+    Ok(())
+}
+
+pub fn assert_valid_holder_discounts(discounts: &[HolderDiscount]) -> Result<()> {
+    if discounts.len() > MAX_HOLDER_DISCOUNTS {
+        return err!(TarsError::TooManyHolderDiscounts);
+    }
+    for discount in discounts {
+        if let HolderDiscountType::Percentage(percentage) = discount.discount {
+            if percentage > 100 {
+                return err!(TarsError::InvalidHolderDiscount);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// HolderDiscountParams
+pub struct HolderDiscountParams<'a: 'b, 'b> {
+    /// discounts
+    pub discounts: &'b [HolderDiscount],
+    /// Remaining accounts from the first proof slot on
+    pub proofs: &'b [AccountInfo<'a>],
+    /// tars
+    pub tars: Pubkey,
+    /// buyer
+    /// CHECK: account checked in CPI
+    pub buyer: AccountInfo<'a>,
+    /// Price the discounts apply to
+    pub base_price: u64,
+    /// Price the buyer pays without a holder discount
+    pub price: u64,
+    /// system_program
+    /// CHECK: account checked in CPI
+    pub system_program: AccountInfo<'a>,
+    /// rent
+    /// CHECK: account checked in CPI
+    pub rent: AccountInfo<'a>,
+}
+
+/// Applies the holder discount giving the lowest price and counts a use of the
+/// NFT that proved it, returning the price to pay. `proofs` hold exactly one
+/// slot per discount, in order: the program id for a discount the buyer doesn't
+/// claim, or the token account and metadata of a held NFT of the discount's
+/// collection followed by its usage PDA if the discount has `uses_per_nft`.
+pub fn apply_holder_discounts(params: HolderDiscountParams<'_, '_>) -> Result<u64> {
+    let HolderDiscountParams {
+        discounts,
+        proofs,
+        tars,
+        buyer,
+        base_price,
+        mut price,
+        system_program,
+        rent,
+    } = params;
+
+    let mut proof_counter: usize = 0;
+    let mut best_usage = None;
+    for discount in discounts {
+        let discount_token_account = proofs
+            .get(proof_counter)
+            .ok_or(TarsError::HolderDiscountProofMismatch)?;
+        proof_counter += 1;
+        if cmp_pubkeys(discount_token_account.key, &crate::id()) {
+            continue;
+        }
+        let discount_metadata = proofs
+            .get(proof_counter)
+            .ok_or(TarsError::HolderDiscountProofMismatch)?;
+        proof_counter += 1;
+
+        let nft_mint = assert_collection_nft(
+            discount_token_account,
+            discount_metadata,
+            buyer.key,
+            &discount.collection,
+        )?
+        .mint;
+        let discount_usage = match discount.uses_per_nft {
+            Some(uses_per_nft) => {
+                let discount_usage = proofs
+                    .get(proof_counter)
+                    .ok_or(TarsError::HolderDiscountProofMismatch)?;
+                proof_counter += 1;
+                Some((discount_usage, nft_mint, uses_per_nft))
+            }
+            None => None,
+        };
+
+        // Whitelist discount_price and holder discounts don't stack, the
+        // lowest price wins.
+        let discounted_price = discount.discount_price(base_price)?;
+        if discounted_price < price {
+            price = discounted_price;
+            best_usage = Some(discount_usage);
+        }
+    }
+    // Holder discount proofs are the last remaining accounts
+    if proof_counter != proofs.len() {
+        return err!(TarsError::HolderDiscountProofMismatch);
+    }
+
+    // Only the NFT that gave the applied discount uses up a use.
+    if let Some(Some((discount_usage, nft_mint, uses_per_nft))) = best_usage {
+        record_nft_usage(NftUsageParams {
+            usage: discount_usage.clone(),
+            prefix: b"holder-discount",
+            tars,
+            nft_mint,
+            max_uses: uses_per_nft,
+            payer: buyer,
+            system_program,
+            rent,
+        })?;
+    }
+    Ok(price)
+}
+
+/// WalletMintParams
+pub struct WalletMintParams<'a> {
+    /// wallet_mint_record
+    /// CHECK: derivation checked in handler
+    pub wallet_mint_record: AccountInfo<'a>,
+    /// tars
+    pub tars: Pubkey,
+    /// wallet
+    /// CHECK: account checked in CPI
+    pub wallet: AccountInfo<'a>,
+    /// cooldown
+    pub cooldown: i64,
+    /// system_program
+    /// CHECK: account checked in CPI
+    pub system_program: AccountInfo<'a>,
+    /// rent
+    /// CHECK: account checked in CPI
+    pub rent: AccountInfo<'a>,
+    /// now
+    pub now: i64,
+}
+
+/// Checks that the wallet's cooldown has passed since its last mint and records
+/// this mint, creating the record PDA on the wallet's first mint.
+pub fn record_wallet_mint(params: WalletMintParams<'_>) -> Result<()> {
+    let WalletMintParams {
+        wallet_mint_record,
+        tars,
+        wallet,
+        cooldown,
+        system_program,
+        rent,
+        now,
+    } = params;
+
+    let seeds = [b"wallet-mint".as_ref(), tars.as_ref(), wallet.key.as_ref()];
+    let (record_key, bump) = Pubkey::find_program_address(&seeds, &crate::id());
+    assert_keys_equal(&record_key, wallet_mint_record.key)?;
+
+    if wallet_mint_record.data_is_empty() {
+        create_or_allocate_account_raw(
+            crate::id(),
+            &wallet_mint_record,
+            &rent,
+            &system_program,
+            &wallet,
+            8 + WalletMintRecord::MAX_SERIALIZED_SIZE,
+            &[
+                b"wallet-mint".as_ref(),
+                tars.as_ref(),
+                wallet.key.as_ref(),
+                &[bump],
+            ],
+        )?;
+        let mut data_ref: &mut [u8] = &mut wallet_mint_record.try_borrow_mut_data()?;
+        WalletMintRecord {
+            tars,
+            wallet: wallet.key(),
+            last_mint_time: i64::MIN,
+        }
+        .try_serialize(&mut data_ref)?;
+    }
+
+    let mut record: Account<WalletMintRecord> = Account::try_from(&wallet_mint_record)?;
+    if now < record.last_mint_time.saturating_add(cooldown) {
+        return err!(TarsError::MintCooldownActive);
+    }
+    record.last_mint_time = now;
+    record.exit(&crate::id())?;
+    Ok(())
+}
+
+/// Checks that `wallet` is not on the blocklist of the tars.
+pub fn assert_not_blocked(
+    blocklist_info: &AccountInfo,
+    tars: &Pubkey,
+    wallet: &Pubkey,
+) -> Result<()> {
+    let (blocklist_key, _) =
+        Pubkey::find_program_address(&[b"blocklist".as_ref(), tars.as_ref()], &crate::id());
+    assert_keys_equal(&blocklist_key, blocklist_info.key)?;
+    let blocklist: Account<Blocklist> = Account::try_from(blocklist_info)?;
+    if blocklist.is_blocked(wallet) {
+        return err!(TarsError::WalletBlocked);
+    }
+    Ok(())
+}
+
+/// Finds the Ed25519 program instruction of the transaction in which `signer`
+/// signed a [`MintVoucher`], and returns the voucher.
+pub fn get_mint_voucher(instruction_sysvar: &AccountInfo, signer: &Pubkey) -> Result<MintVoucher> {
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instruction_sysvar) {
+        index += 1;
+        if !cmp_pubkeys(&ix.program_id, &ed25519_program::id()) {
+            continue;
+        }
+
+        // Only a single signature with its public key, signature and message all
+        // in this instruction is accepted.
+        let data = &ix.data;
+        if data.len() < 16 || data[0] != 1 {
+            continue;
+        }
+        let read_u16 = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let public_key_offset = read_u16(6) as usize;
+        let message_offset = read_u16(10) as usize;
+        let message_size = read_u16(12) as usize;
+        if read_u16(4) != u16::MAX || read_u16(8) != u16::MAX || read_u16(14) != u16::MAX {
+            continue;
+        }
+
+        let public_key = match data.get(public_key_offset..public_key_offset + 32) {
+            Some(public_key) => public_key,
+            None => continue,
+        };
+        if public_key != signer.as_ref() {
+            continue;
+        }
+        let message = data
+            .get(message_offset..message_offset + message_size)
+            .ok_or(TarsError::InvalidMintVoucher)?;
+        return MintVoucher::try_from_slice(message)
+            .map_err(|_| error!(TarsError::InvalidMintVoucher));
+    }
+    err!(TarsError::MissingMintVoucher)
+}
+
+/// MintVoucherParams
+pub struct MintVoucherParams<'a: 'b, 'b> {
+    /// instruction_sysvar
+    /// CHECK: address checked in handler
+    pub instruction_sysvar: &'b AccountInfo<'a>,
+    /// voucher_record
+    /// CHECK: derivation checked in handler
+    pub voucher_record: AccountInfo<'a>,
+    /// authorized_signer
+    pub authorized_signer: Pubkey,
+    /// tars
+    pub tars: Pubkey,
+    /// buyer
+    /// CHECK: account checked in CPI
+    pub buyer: AccountInfo<'a>,
+    /// system_program
+    /// CHECK: account checked in CPI
+    pub system_program: AccountInfo<'a>,
+    /// rent
+    /// CHECK: account checked in CPI
+    pub rent: AccountInfo<'a>,
+    /// now
+    pub now: i64,
+}
+
+/// Checks the mint voucher of the transaction and counts one use of it in its
+/// record PDA, returning the price the voucher grants.
+pub fn use_mint_voucher(params: MintVoucherParams<'_, '_>) -> Result<u64> {
+    let MintVoucherParams {
+        instruction_sysvar,
+        voucher_record,
+        authorized_signer,
+        tars,
+        buyer,
+        system_program,
+        rent,
+        now,
+    } = params;
+
+    let voucher = get_mint_voucher(instruction_sysvar, &authorized_signer)?;
+    if !cmp_pubkeys(&voucher.tars, &tars) || !cmp_pubkeys(&voucher.buyer, buyer.key) {
+        return err!(TarsError::InvalidMintVoucher);
+    }
+    if now > voucher.expiry {
+        return err!(TarsError::MintVoucherExpired);
+    }
+
+    let nonce = voucher.nonce.to_le_bytes();
+    let seeds = [
+        b"mint-voucher".as_ref(),
+        tars.as_ref(),
+        buyer.key.as_ref(),
+        &nonce,
+    ];
+    let (record_key, bump) = Pubkey::find_program_address(&seeds, &crate::id());
+    assert_keys_equal(&record_key, voucher_record.key)?;
+
+    if voucher_record.data_is_empty() {
+        create_or_allocate_account_raw(
+            crate::id(),
+            &voucher_record,
+            &rent,
+            &system_program,
+            &buyer,
+            8 + MintVoucherRecord::MAX_SERIALIZED_SIZE,
+            &[
+                b"mint-voucher".as_ref(),
+                tars.as_ref(),
+                buyer.key.as_ref(),
+                &nonce,
+                &[bump],
+            ],
+        )?;
+        let mut data_ref: &mut [u8] = &mut voucher_record.try_borrow_mut_data()?;
+        MintVoucherRecord {
+            tars,
+            buyer: buyer.key(),
+            nonce: voucher.nonce,
+            uses: 0,
+        }
+        .try_serialize(&mut data_ref)?;
+    }
+
+    let mut record: Account<MintVoucherRecord> = Account::try_from(&voucher_record)?;
+    if record.uses >= voucher.max_quantity {
+        return err!(TarsError::MintVoucherExhausted);
+    }
+    record.uses = record
+        .uses
+        .checked_add(1)
+        .ok_or(TarsError::NumericalOverflowError)?;
+    record.exit(&crate::id())?;
+    Ok(voucher.price)
+}
+
+/// Number of config lines in every config page of the tars, if its config
+/// lines live in config pages instead of the tars account.
+pub fn config_page_lines(data: &TarsData) -> Option<usize> {
+    match (&data.config_pages, &data.hidden_settings) {
+        (Some(config_pages), None) => Some(config_pages.lines_per_page as usize),
+        _ => None,
+    }
+}
+
+/// Number of config pages holding the lines of the tars.
+pub fn config_page_count(items_available: u64, lines_per_page: usize) -> usize {
+    (items_available as usize + lines_per_page - 1) / lines_per_page
+}
+
+/// Bytes taken by each bitmask of a config page.
+pub fn config_page_mask_len(lines_per_page: usize) -> usize {
+    (lines_per_page + 7) / 8
+}
+
+/// Start of the config lines of a config page, after its two bitmasks.
+pub fn config_page_lines_start(lines_per_page: usize) -> usize {
+    CONFIG_PAGE_MASKS_START + 2 * config_page_mask_len(lines_per_page)
+}
+
+pub fn config_page_space(lines_per_page: usize, line_size: usize) -> usize {
+    config_page_lines_start(lines_per_page) + lines_per_page * line_size
+}
+
+/// Splits the data of a config page into its bitmask of loaded lines, its
+/// bitmask of minted lines and its config lines.
+pub fn split_config_page(
+    data: &mut [u8],
+    lines_per_page: usize,
+) -> (&mut [u8], &mut [u8], &mut [u8]) {
+    let mask_len = config_page_mask_len(lines_per_page);
+    let (loaded, rest) = data[CONFIG_PAGE_MASKS_START..].split_at_mut(mask_len);
+    let (redeemed, lines) = rest.split_at_mut(mask_len);
+    (loaded, redeemed, lines)
+}
+
+// Config page bitmasks keep line `slot` at bit `slot % 8` of byte `slot / 8`,
+// so pages of 32 lines are laid out as with the former `u32` bitmasks.
+pub fn is_page_line_set(mask: &[u8], slot: usize) -> bool {
+    mask[slot / 8] & (1 << (slot % 8)) != 0
+}
+
+pub fn set_page_line(mask: &mut [u8], slot: usize) {
+    mask[slot / 8] |= 1 << (slot % 8);
+}
+
+pub fn clear_page_line(mask: &mut [u8], slot: usize) {
+    mask[slot / 8] &= !(1 << (slot % 8));
+}
+
+/// Checks that `config_page_info` is the config page at `page_index` of `tars`.
+pub fn assert_config_page(
+    config_page_info: &AccountInfo,
+    tars: &Pubkey,
+    page_index: usize,
+) -> Result<()> {
+    let config_page: Account<ConfigPage> = Account::try_from(config_page_info)?;
+    if !cmp_pubkeys(&config_page.tars, tars) || config_page.index as usize != page_index {
+        return err!(TarsError::InvalidConfigPage);
+    }
+    Ok(())
+}
+
+/// Config pages must hold at least one line, and fit in an account the
+/// program can create.
+pub fn assert_valid_config_pages(data: &TarsData) -> Result<()> {
+    if let Some(config_pages) = &data.config_pages {
+        if data.hidden_settings.is_some() {
+            return err!(TarsError::HiddenSettingsConfigsDoNotHaveConfigLines);
+        }
+        let lines_per_page = config_pages.lines_per_page as usize;
+        if lines_per_page == 0
+            || config_page_space(lines_per_page, config_line_size(data))
+                > MAX_PERMITTED_DATA_INCREASE
+        {
+            return err!(TarsError::InvalidConfigPageSettings);
+        }
+    }
+    Ok(())
+}
+
+/// Marks and returns the page at `choice`, modulo their number, among the pages
+/// not marked in `used_pages` yet.
+pub fn pick_unused_config_page(
+    used_pages: &mut [u8],
+    page_count: usize,
+    choice: usize,
+) -> Option<usize> {
+    let unused = (0..page_count)
+        .filter(|page| used_pages[page / 8] & (0x80 >> (page % 8)) == 0)
+        .count();
+    if unused == 0 {
+        return None;
+    }
+    let mut skip = choice % unused;
+    for page in 0..page_count {
+        let mask = 0x80 >> (page % 8);
+        if used_pages[page / 8] & mask == 0 {
+            if skip == 0 {
+                used_pages[page / 8] |= mask;
+                return Some(page);
+            }
+            skip -= 1;
+        }
+    }
+    None
+}
+
+/// First line of a config page, from `index` modulo the lines in the page,
+/// that isn't marked in its `redeemed` bitmask.
+pub fn pick_config_page_line(redeemed: &[u8], lines_in_page: usize, index: usize) -> Option<usize> {
+    (0..lines_in_page)
+        .map(|i| (index + i) % lines_in_page)
+        .find(|i| !is_page_line_set(redeemed, *i))
+}
+
+/// Redeems a pseudo-random line of the config page being minted.
+///
+/// Minters pass the page, so it has to be known before the mint. Pages are
+/// minted out one at a time, and when one runs out the next is picked
+/// pseudo-randomly among the pages not minted from yet, from the slot hash of
+/// that mint. Unlike selection over the whole collection, the lines left in
+/// the current page can be read ahead, but which page follows can't. Minting
+/// starts on page 0.
+pub fn get_config_page_line(
+    tars: &Account<Tars>,
+    config_page_info: &AccountInfo,
+    lines_per_page: usize,
+    index: usize,
+) -> Result<ConfigLine> {
+    let tars_info = tars.to_account_info();
+    let mut tars_data = tars_info.data.borrow_mut();
+    // Pages added by growing the tars must be loaded before minting resumes.
+    if get_config_count(&tars_data)? < tars.data.items_available as usize {
+        return err!(TarsError::TarsNotFullyLoaded);
+    }
+
+    let page_count = config_page_count(tars.data.items_available, lines_per_page);
+    let used_pages_end = USED_CONFIG_PAGES_START + page_count / 8 + 1;
+    let cursor = u32::from_le_bytes(*array_ref![tars_data, CONFIG_PAGE_CURSOR_START, 4]) as usize;
+    let page_index = if cursor < page_count {
+        tars_data[USED_CONFIG_PAGES_START + cursor / 8] |= 0x80 >> (cursor % 8);
+        cursor
+    } else {
+        // All pages had run out before the tars grew, continue with the first new one
+        pick_unused_config_page(
+            &mut tars_data[USED_CONFIG_PAGES_START..used_pages_end],
+            page_count,
+            0,
+        )
+        .ok_or(TarsError::CannotFindUsableConfigLine)?
+    };
+
+    assert_config_page(config_page_info, &tars.key(), page_index)?;
+    let mut data = config_page_info.data.borrow_mut();
+    let (loaded, redeemed, lines) = split_config_page(&mut data, lines_per_page);
+
+    // The last page can be partially used
+    let lines_in_page =
+        (tars.data.items_available as usize - page_index * lines_per_page).min(lines_per_page);
+    let line_index = pick_config_page_line(redeemed, lines_in_page, index)
+        .ok_or(TarsError::CannotFindUsableConfigLine)?;
+    if !is_page_line_set(loaded, line_index) {
+        return err!(TarsError::TarsNotFullyLoaded);
+    }
+    set_page_line(redeemed, line_index);
+
+    let minted: u32 = redeemed.iter().map(|byte| byte.count_ones()).sum();
+    let next_page = if minted as usize == lines_in_page {
+        pick_unused_config_page(
+            &mut tars_data[USED_CONFIG_PAGES_START..used_pages_end],
+            page_count,
+            index / lines_per_page,
+        )
+        .map_or(u32::MAX, |page| page as u32)
+    } else {
+        page_index as u32
+    };
+    tars_data[CONFIG_PAGE_CURSOR_START..CONFIG_PAGE_CURSOR_START + 4]
+        .copy_from_slice(&next_page.to_le_bytes());
+
+    let line_size = config_line_size(&tars.data);
+    let start = line_index * line_size;
+    parse_config_line(
+        &lines[start..start + line_size],
+        &tars.data.config_line_template,
+    )
+}
+
+/// Whether the config lines of the tars can no longer change, either locked
+/// by the authority or by the first mint.
+pub fn is_config_locked(tars: &Tars) -> bool {
+    tars.items_redeemed > 0 || tars.is_feature_active(CONFIG_LOCKED_FEATURE_INDEX)
+}
+
+/// Bytes taken by every config line of the tars.
+pub fn config_line_size(data: &TarsData) -> usize {
+    match &data.config_line_template {
+        Some(template) => {
+            template.name_suffix_length as usize + template.uri_suffix_length as usize
+        }
+        None => CONFIG_LINE_SIZE,
+    }
+}
+
+pub fn assert_valid_config_line_template(template: &Option<ConfigLineTemplate>) -> Result<()> {
+    if let Some(template) = template {
+        if template.name_prefix.len() + template.name_suffix_length as usize > MAX_NAME_LENGTH
+            || template.uri_prefix.len() + template.uri_suffix_length as usize > MAX_URI_LENGTH
+            || template.name_suffix_length == 0 && template.uri_suffix_length == 0
+        {
+            return err!(TarsError::InvalidConfigLineTemplate);
+        }
+    }
+    Ok(())
+}
+
+/// Writes a config line with its name and uri padded by zeroes, only their
+/// suffixes if the tars has a config line template.
+pub fn serialize_config_line(
+    line: &ConfigLine,
+    template: &Option<ConfigLineTemplate>,
+) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(CONFIG_LINE_SIZE);
+    match template {
+        Some(template) => {
+            bytes.append(&mut pad_config_value(
+                &line.name,
+                template.name_suffix_length as usize,
+            )?);
+            bytes.append(&mut pad_config_value(
+                &line.uri,
+                template.uri_suffix_length as usize,
+            )?);
+        }
+        // Same bytes as borsh writes for the padded strings
+        None => {
+            bytes.extend_from_slice(&(MAX_NAME_LENGTH as u32).to_le_bytes());
+            bytes.append(&mut pad_config_value(&line.name, MAX_NAME_LENGTH)?);
+            bytes.extend_from_slice(&(MAX_URI_LENGTH as u32).to_le_bytes());
+            bytes.append(&mut pad_config_value(&line.uri, MAX_URI_LENGTH)?);
+        }
+    }
+    Ok(bytes)
+}
+
+fn pad_config_value(value: &str, len: usize) -> Result<Vec<u8>> {
+    if value.len() > len {
+        return err!(TarsError::ConfigLineTooLong);
+    }
+    let mut bytes = value.as_bytes().to_vec();
+    bytes.resize(len, 0);
+    Ok(bytes)
+}
+
+/// Reads a config line written by [`serialize_config_line`].
+pub fn parse_config_line(data: &[u8], template: &Option<ConfigLineTemplate>) -> Result<ConfigLine> {
+    let unpad = |bytes: &[u8]| {
+        let len = bytes
+            .iter()
+            .position(|byte| *byte == 0)
+            .unwrap_or(bytes.len());
+        String::from_utf8(bytes[..len].to_vec()).map_err(|_| error!(TarsError::InvalidString))
+    };
+    match template {
+        Some(template) => {
+            let name_end = template.name_suffix_length as usize;
+            let uri_end = name_end + template.uri_suffix_length as usize;
+            Ok(ConfigLine {
+                name: template.name_prefix.clone() + &unpad(&data[..name_end])?,
+                uri: template.uri_prefix.clone() + &unpad(&data[name_end..uri_end])?,
+            })
+        }
+        None => Ok(ConfigLine {
+            name: unpad(&data[4..4 + MAX_NAME_LENGTH])?,
+            uri: unpad(&data[8 + MAX_NAME_LENGTH..8 + MAX_NAME_LENGTH + MAX_URI_LENGTH])?,
+        }),
+    }
+}
+
+/// Checks that `proof` links the revealed config line of `mint_number` to the
+/// Merkle `root`. Leaves are `hash(0, mint_number, borsh(config_line))` and
+/// nodes `hash(1, lower child, higher child)`.
+pub fn verify_reveal_proof(
+    proof: &[[u8; 32]],
+    root: &[u8; 32],
+    mint_number: u64,
+    config_line: &ConfigLine,
+) -> Result<bool> {
+    let mut node =
+        hashv(&[&[0], &mint_number.to_le_bytes(), &config_line.try_to_vec()?]).to_bytes();
+    for sibling in proof {
+        node = if node <= *sibling {
+            hashv(&[&[1], &node, sibling])
+        } else {
+            hashv(&[&[1], sibling, &node])
+        }
+        .to_bytes();
+    }
+    Ok(node == *root)
+}
+
+/// Loads the reveal schedule of a tars with staged reveals, passed first in the
+/// remaining accounts.
+pub fn load_reveal_schedule<'a>(
+    tars: &Account<Tars>,
+    remaining_accounts: &[AccountInfo<'a>],
+) -> Result<Option<Account<'a, RevealSchedule>>> {
+    if !tars.is_feature_active(STAGED_REVEAL_FEATURE_INDEX) {
+        return Ok(None);
+    }
+    let reveal_schedule_info = remaining_accounts
+        .first()
+        .ok_or(TarsError::MissingRevealSchedule)?;
+    let reveal_schedule: Account<RevealSchedule> = Account::try_from(reveal_schedule_info)?;
+    if !cmp_pubkeys(&reveal_schedule.tars, &tars.key()) {
+        return err!(TarsError::MissingRevealSchedule);
+    }
+    Ok(Some(reveal_schedule))
+}
+
+/// Merkle root the NFT of `mint_number` is revealed against, from its unlocked
+/// stage with staged reveals and from the hidden settings otherwise.
+pub fn get_reveal_root(
+    tars: &Tars,
+    reveal_schedule: Option<&RevealSchedule>,
+    mint_number: u64,
+    now: i64,
+) -> Result<[u8; 32]> {
+    let hidden_settings = match &tars.data.hidden_settings {
+        Some(hidden_settings) if tars.is_feature_active(REVEAL_FEATURE_INDEX) => hidden_settings,
+        _ => return err!(TarsError::RevealNotCommitted),
+    };
+    match reveal_schedule {
+        Some(reveal_schedule) => {
+            let stage = reveal_schedule
+                .stage(mint_number)
+                .ok_or(TarsError::NftNotRevealable)?;
+            if now < stage.unlock_time {
+                return err!(TarsError::RevealStageLocked);
+            }
+            Ok(stage.root)
+        }
+        None => Ok(hidden_settings.hash),
+    }
+}
+
+/// MintReceiptParams
+pub struct MintReceiptParams<'a> {
+    /// mint_receipt
+    /// CHECK: derivation checked in function
+    pub mint_receipt: AccountInfo<'a>,
+    /// tars
+    pub tars: Pubkey,
+    /// mint_number
+    pub mint_number: u64,
+    /// metadata
+    pub metadata: Pubkey,
+    /// payer
+    /// CHECK: account checked in CPI
+    pub payer: AccountInfo<'a>,
+    /// system_program
+    /// CHECK: account checked in CPI
+    pub system_program: AccountInfo<'a>,
+    /// rent
+    /// CHECK: account checked in CPI
+    pub rent: AccountInfo<'a>,
+}
+
+/// Creates the receipt PDA of the NFT minted as `mint_number`, which the
+/// reveal crank reads to find the metadata to reveal.
+pub fn record_mint_receipt(params: MintReceiptParams<'_>) -> Result<()> {
+    let MintReceiptParams {
+        mint_receipt,
+        tars,
+        mint_number,
+        metadata,
+        payer,
+        system_program,
+        rent,
+    } = params;
+
+    let mint_number_bytes = mint_number.to_le_bytes();
+    let seeds = [b"mint-receipt".as_ref(), tars.as_ref(), &mint_number_bytes];
+    let (receipt_key, bump) = Pubkey::find_program_address(&seeds, &crate::id());
+    assert_keys_equal(&receipt_key, mint_receipt.key)?;
+
+    create_or_allocate_account_raw(
+        crate::id(),
+        &mint_receipt,
+        &rent,
+        &system_program,
+        &payer,
+        8 + MintReceipt::MAX_SERIALIZED_SIZE,
+        &[
+            b"mint-receipt".as_ref(),
+            tars.as_ref(),
+            &mint_number_bytes,
+            &[bump],
+        ],
+    )?;
+    let mut data_ref: &mut [u8] = &mut mint_receipt.try_borrow_mut_data()?;
+    MintReceipt {
+        tars,
+        mint_number,
+        metadata,
+        revealed: false,
+    }
+    .try_serialize(&mut data_ref)?;
+    Ok(())
+}
+
+pub struct RevealParams<'a: 'b, 'b> {
+    /// tars
+    pub tars: &'b Account<'a, Tars>,
+    /// tars_creator
+    /// CHECK: derivation checked in handler
+    pub tars_creator: AccountInfo<'a>,
+    /// creator_bump
+    pub creator_bump: u8,
+    /// metadata
+    /// CHECK: checked in function and CPI
+    pub metadata: AccountInfo<'a>,
+    /// token_metadata_program
+    /// CHECK: checked in handler
+    pub token_metadata_program: AccountInfo<'a>,
+    /// Merkle root from [`get_reveal_root`]
+    pub root: [u8; 32],
+    /// reveal
+    pub reveal: NftReveal,
+}
+
+/// Updates the metadata of an NFT minted by the tars with the placeholder of
+/// its hidden settings to its proven config line, and hands its update
+/// authority to the tars authority.
+pub fn reveal_nft_metadata(params: RevealParams<'_, '_>) -> Result<()> {
+    let RevealParams {
+        tars,
+        tars_creator,
+        creator_bump,
+        metadata,
+        token_metadata_program,
+        root,
+        reveal,
+    } = params;
+    assert_owned_by(&metadata, &mpl_token_metadata::id())?;
+    let hidden_settings = tars
+        .data
+        .hidden_settings
+        .as_ref()
+        .ok_or(TarsError::RevealRequiresHiddenSettings)?;
+
+    let metadata_data = Metadata::from_account_info(&metadata)?;
+    // Only NFTs minted by the tars have its creator verified first, and only
+    // unrevealed ones still carry the placeholder name of their mint number.
+    let minted_by_tars = metadata_data
+        .data
+        .creators
+        .as_ref()
+        .and_then(|creators| creators.first())
+        .map_or(false, |creator| {
+            creator.verified && cmp_pubkeys(&creator.address, tars_creator.key)
+        });
+    let placeholder = hidden_settings.name.clone() + "#" + &(reveal.mint_number + 1).to_string();
+    if !minted_by_tars
+        || !cmp_pubkeys(&metadata_data.update_authority, tars_creator.key)
+        || metadata_data.data.name.trim_end_matches(char::from(0)) != placeholder
+    {
+        return err!(TarsError::NftNotRevealable);
+    }
+
+    if !verify_reveal_proof(
+        &reveal.proof,
+        &root,
+        reveal.mint_number,
+        &reveal.config_line,
+    )? {
+        return err!(TarsError::InvalidRevealProof);
+    }
+
+    let tars_key = tars.key();
+    let authority_seeds = [PREFIX.as_bytes(), tars_key.as_ref(), &[creator_bump]];
+    invoke_signed(
+        &update_metadata_accounts_v2(
+            token_metadata_program.key(),
+            metadata.key(),
+            tars_creator.key(),
+            Some(tars.authority),
+            Some(DataV2 {
+                name: reveal.config_line.name,
+                symbol: metadata_data.data.symbol,
+                uri: reveal.config_line.uri,
+                seller_fee_basis_points: metadata_data.data.seller_fee_basis_points,
+                creators: metadata_data.data.creators,
+                collection: metadata_data.collection,
+                uses: metadata_data.uses,
+            }),
+            None,
+            if !tars.data.is_mutable {
+                Some(false)
+            } else {
+                None
+            },
+        ),
+        &[token_metadata_program, metadata, tars_creator],
+        &[&authority_seeds],
+    )?;
+    Ok(())
+}
+
 pub fn is_feature_active(uuid: &str, feature_index: usize) -> bool {
     uuid.as_bytes()[feature_index] == b"1"[0]
 }
@@ -275,6 +1437,314 @@ pub mod tests {
         println!("Should be 000000: {}", uuid);
     }
 
+    #[test]
+    fn picks_only_unused_config_pages() {
+        let page_count = 10;
+        let mut used_pages = vec![0u8; page_count / 8 + 1];
+        used_pages[0] = 0b1010_0000; // pages 0 and 2
+
+        // choices count unused pages only: 1, 3, 4, ...
+        assert_eq!(
+            pick_unused_config_page(&mut used_pages, page_count, 1),
+            Some(3)
+        );
+        assert_eq!(used_pages[0], 0b1011_0000);
+        // and wrap around the number of unused pages left
+        assert_eq!(
+            pick_unused_config_page(&mut used_pages, page_count, 7),
+            Some(1)
+        );
+
+        let mut picked = vec![0, 1, 2, 3];
+        while let Some(page) = pick_unused_config_page(&mut used_pages, page_count, 5) {
+            assert!(!picked.contains(&page));
+            picked.push(page);
+        }
+        assert_eq!(picked.len(), page_count);
+        assert_eq!(config_page_count(320, 32), 10);
+        assert_eq!(config_page_count(321, 32), 11);
+        assert_eq!(config_page_count(321, 500), 1);
+    }
+
+    #[test]
+    fn picks_unredeemed_config_page_lines() {
+        // pages are minted from the line at `index`, wrapping around the page
+        assert_eq!(pick_config_page_line(&[0; 4], 32, 37), Some(5));
+        assert_eq!(
+            pick_config_page_line(&[0b0110_0000, 0, 0, 0], 32, 5),
+            Some(7)
+        );
+        assert_eq!(pick_config_page_line(&[0, 0, 0, 0x80], 32, 31), Some(0));
+        // pages span as many bitmask bytes as they need
+        assert_eq!(pick_config_page_line(&[0xff, 0b0001], 10, 3), Some(9));
+
+        // the last page only picks among the lines it holds
+        assert_eq!(pick_config_page_line(&[0b0011], 3, 1), Some(2));
+        assert_eq!(pick_config_page_line(&[0b0110], 3, 4), Some(0));
+        assert_eq!(pick_config_page_line(&[0b0111], 3, 0), None);
+        assert_eq!(pick_config_page_line(&[0xff; 4], 32, 9), None);
+    }
+
+    fn config_line(name: &str, uri: &str) -> ConfigLine {
+        ConfigLine {
+            name: String::from(name),
+            uri: String::from(uri),
+        }
+    }
+
+    #[test]
+    fn config_lines_round_trip() {
+        let line = config_line("Tars #1", "https://arweave.net/tars-1");
+        let bytes = serialize_config_line(&line, &None).unwrap();
+        assert_eq!(bytes.len(), CONFIG_LINE_SIZE);
+        let parsed = parse_config_line(&bytes, &None).unwrap();
+        assert_eq!(parsed.name, line.name);
+        assert_eq!(parsed.uri, line.uri);
+        // untemplated lines keep the borsh layout of the padded strings
+        let padded = ConfigLine::try_from_slice(&bytes).unwrap();
+        assert_eq!(padded.name.len(), MAX_NAME_LENGTH);
+        assert_eq!(padded.uri.len(), MAX_URI_LENGTH);
+
+        let template = Some(ConfigLineTemplate {
+            name_prefix: String::from("Tars #"),
+            uri_prefix: String::from("https://arweave.net/"),
+            name_suffix_length: 4,
+            uri_suffix_length: 10,
+        });
+        assert!(assert_valid_config_line_template(&template).is_ok());
+        let suffixes = config_line("12", "tars-12");
+        let bytes = serialize_config_line(&suffixes, &template).unwrap();
+        assert_eq!(bytes.len(), 14);
+        let parsed = parse_config_line(&bytes, &template).unwrap();
+        assert_eq!(parsed.name, "Tars #12");
+        assert_eq!(parsed.uri, "https://arweave.net/tars-12");
+
+        // suffixes filling their whole length have no padding to strip
+        let full = config_line("1234", "0123456789");
+        let bytes = serialize_config_line(&full, &template).unwrap();
+        let parsed = parse_config_line(&bytes, &template).unwrap();
+        assert_eq!(parsed.name, "Tars #1234");
+        assert_eq!(parsed.uri, "https://arweave.net/0123456789");
+
+        assert!(serialize_config_line(&config_line("12345", "tars"), &template).is_err());
+        assert!(serialize_config_line(&config_line("12", "01234567890"), &template).is_err());
+    }
+
+    fn reveal_leaf(mint_number: u64, line: &ConfigLine) -> [u8; 32] {
+        hashv(&[
+            &[0],
+            &mint_number.to_le_bytes(),
+            &line.try_to_vec().unwrap(),
+        ])
+        .to_bytes()
+    }
+
+    fn reveal_node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        if a <= b {
+            hashv(&[&[1], a, b]).to_bytes()
+        } else {
+            hashv(&[&[1], b, a]).to_bytes()
+        }
+    }
+
+    #[test]
+    fn verifies_reveal_proofs() {
+        let lines: Vec<ConfigLine> = (1..=4)
+            .map(|i| {
+                config_line(
+                    &format!("Tars #{}", i),
+                    &format!("https://arweave.net/{}", i),
+                )
+            })
+            .collect();
+        let leaves: Vec<[u8; 32]> = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| reveal_leaf(i as u64 + 1, line))
+            .collect();
+        let left = reveal_node(&leaves[0], &leaves[1]);
+        let right = reveal_node(&leaves[2], &leaves[3]);
+        let root = reveal_node(&left, &right);
+
+        let proof = [leaves[0], right];
+        assert!(verify_reveal_proof(&proof, &root, 2, &lines[1]).unwrap());
+        let proof = [leaves[3], left];
+        assert!(verify_reveal_proof(&proof, &root, 3, &lines[2]).unwrap());
+
+        // the leaf commits to the mint number
+        let proof = [leaves[0], right];
+        assert!(!verify_reveal_proof(&proof, &root, 1, &lines[1]).unwrap());
+        assert!(!verify_reveal_proof(&proof, &root, 3, &lines[1]).unwrap());
+
+        // lines can't be swapped between mint numbers
+        assert!(!verify_reveal_proof(&proof, &root, 2, &lines[2]).unwrap());
+        let swapped = config_line("Tars #2", "https://arweave.net/3");
+        assert!(!verify_reveal_proof(&proof, &root, 2, &swapped).unwrap());
+
+        // a single line is its own root
+        let single_root = reveal_leaf(7, &lines[0]);
+        assert!(verify_reveal_proof(&[], &single_root, 7, &lines[0]).unwrap());
+        assert!(!verify_reveal_proof(&[], &single_root, 7, &lines[1]).unwrap());
+        assert!(!verify_reveal_proof(&[], &root, 2, &lines[1]).unwrap());
+    }
+
+    fn holder_discount(collection: Pubkey, discount: HolderDiscountType) -> HolderDiscount {
+        HolderDiscount {
+            collection,
+            discount,
+            uses_per_nft: None,
+        }
+    }
+
+    // Token account and metadata of an NFT of `collection` held by `owner`
+    fn held_nft(owner: &Pubkey, collection: Pubkey) -> ([Pubkey; 2], [Vec<u8>; 2]) {
+        let mint = Pubkey::new_unique();
+        let (metadata_key, _) = Pubkey::find_program_address(
+            &[
+                mpl_token_metadata::state::PREFIX.as_bytes(),
+                mpl_token_metadata::id().as_ref(),
+                mint.as_ref(),
+            ],
+            &mpl_token_metadata::id(),
+        );
+        let mut token_account = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint,
+            owner: *owner,
+            amount: 1,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut token_account);
+        let metadata = Metadata {
+            key: mpl_token_metadata::state::Key::MetadataV1,
+            update_authority: Pubkey::new_unique(),
+            mint,
+            data: mpl_token_metadata::state::Data {
+                name: String::from("Partner #1"),
+                symbol: String::from("PRT"),
+                uri: String::from("https://arweave.net/partner"),
+                seller_fee_basis_points: 0,
+                creators: None,
+            },
+            primary_sale_happened: true,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: None,
+            collection: Some(mpl_token_metadata::state::Collection {
+                verified: true,
+                key: collection,
+            }),
+            uses: None,
+        };
+        (
+            [Pubkey::new_unique(), metadata_key],
+            [token_account, metadata.try_to_vec().unwrap()],
+        )
+    }
+
+    fn discounted_price<'a>(
+        discounts: &[HolderDiscount],
+        buyer: &AccountInfo<'a>,
+        proofs: &[AccountInfo<'a>],
+    ) -> core::result::Result<u64, ProgramError> {
+        apply_holder_discounts(HolderDiscountParams {
+            discounts,
+            proofs,
+            tars: Pubkey::new_unique(),
+            buyer: buyer.clone(),
+            base_price: 1_000,
+            price: 1_000,
+            system_program: buyer.clone(),
+            rent: buyer.clone(),
+        })
+        .map_err(Into::into)
+    }
+
+    #[test]
+    fn pairs_holder_discount_proofs_with_their_discounts() {
+        let buyer_key = Pubkey::new_unique();
+        let (gold, silver) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let discounts = [
+            holder_discount(gold, HolderDiscountType::Price(100)),
+            holder_discount(silver, HolderDiscountType::Percentage(50)),
+        ];
+        let (nft_keys, [mut token_account, mut metadata]) = held_nft(&buyer_key, silver);
+
+        let program_id = crate::id();
+        let system_program_id = System::id();
+        let token_program_id = spl_token::id();
+        let token_metadata_program_id = mpl_token_metadata::id();
+        let (mut buyer_lamports, mut unclaimed_lamports) = (1, 1);
+        let (mut token_account_lamports, mut metadata_lamports) = (1, 1);
+        let (mut buyer_data, mut unclaimed_data) = (vec![], vec![]);
+        let buyer = AccountInfo::new(
+            &buyer_key,
+            true,
+            true,
+            &mut buyer_lamports,
+            &mut buyer_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        let unclaimed = AccountInfo::new(
+            &program_id,
+            false,
+            false,
+            &mut unclaimed_lamports,
+            &mut unclaimed_data,
+            &program_id,
+            true,
+            0,
+        );
+        let token_account = AccountInfo::new(
+            &nft_keys[0],
+            false,
+            false,
+            &mut token_account_lamports,
+            &mut token_account,
+            &token_program_id,
+            false,
+            0,
+        );
+        let metadata = AccountInfo::new(
+            &nft_keys[1],
+            false,
+            false,
+            &mut metadata_lamports,
+            &mut metadata,
+            &token_metadata_program_id,
+            false,
+            0,
+        );
+
+        let proofs = [unclaimed.clone(), token_account.clone(), metadata.clone()];
+        assert_eq!(discounted_price(&discounts, &buyer, &proofs), Ok(500));
+        let proofs = [unclaimed.clone(), unclaimed.clone()];
+        assert_eq!(discounted_price(&discounts, &buyer, &proofs), Ok(1_000));
+
+        // the NFT only proves the discount of its own collection
+        let proofs = [token_account.clone(), metadata.clone(), unclaimed.clone()];
+        assert_eq!(
+            discounted_price(&discounts, &buyer, &proofs),
+            Err(ProgramError::Custom(TarsError::InvalidCollectionNft.into()))
+        );
+
+        // and every discount needs its slot, with nothing after the last one
+        let mismatch = Err(ProgramError::Custom(
+            TarsError::HolderDiscountProofMismatch.into(),
+        ));
+        assert_eq!(
+            discounted_price(&discounts, &buyer, &[unclaimed.clone()]),
+            mismatch
+        );
+        let proofs = [unclaimed.clone(), token_account.clone()];
+        assert_eq!(discounted_price(&discounts, &buyer, &proofs), mismatch);
+        let proofs = [unclaimed.clone(), unclaimed.clone(), unclaimed];
+        assert_eq!(discounted_price(&discounts, &buyer, &proofs), mismatch);
+    }
+
     #[test]
     fn check_keys_equal() {
         let key1 = Pubkey::new_unique();
@@ -1,9 +1,15 @@
+use crate::whitelist_config::{WhitelistConfig, WhitelistTierConfig};
+use crate::TarsError;
 use anchor_lang::prelude::*;
+use proc_macros::MaxSerializedSize;
 use std::fmt::Debug;
+use std::str::FromStr;
 
-//#[proc_macros::assert_size(4)]
+#[proc_macros::assert_size(borsh = 1)]
 #[repr(C)]
-#[derive(Debug, Clone, Copy, AnchorDeserialize, AnchorSerialize, PartialEq, PartialOrd)]
+#[derive(
+    Debug, Clone, Copy, AnchorDeserialize, AnchorSerialize, PartialEq, PartialOrd, MaxSerializedSize,
+)]
 pub enum WLType {
     Null,
     One,
@@ -12,15 +18,87 @@ pub enum WLType {
     Four,
 }
 
-//#[proc_macros::assert_size(88)]
+impl WLType {
+    /// Number of mint spots a wallet of this tier is granted.
+    pub fn spots(&self) -> u64 {
+        match self {
+            WLType::Null => 0,
+            WLType::One => 1,
+            WLType::Two => 2,
+            WLType::Three => 3,
+            WLType::Four => 4,
+        }
+    }
+}
+
+impl FromStr for WLType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "One" => Ok(WLType::One),
+            "Two" => Ok(WLType::Two),
+            "Three" => Ok(WLType::Three),
+            "Four" => Ok(WLType::Four),
+            _ => err!(TarsError::InvalidWLType),
+        }
+    }
+}
+
+#[proc_macros::assert_size(borsh = 131)]
 #[repr(C)]
 #[account]
-#[derive(Debug)]
+#[derive(Debug, MaxSerializedSize)]
 pub struct WalletWhitelist {
     pub tars_creator: Pubkey,               //32
     pub whitelisted_address: Pubkey,             //32
     pub whitelist_type: WLType,                  //4
     pub number_of_whitelist_spots_per_user: u64, //8
-    pub discounted_mint_price: u64,              //8
-    pub whitelist_mint_start_time: u64,          //8
+    /// Overrides the tier's `discounted_mint_price` for this wallet.
+    pub discounted_mint_price: Option<u64>, //9
+    /// Overrides the tier's `whitelist_mint_start_time` for this wallet.
+    pub whitelist_mint_start_time: Option<u64>, //9
+    /// Receives the rent back when the account is closed.
+    pub funder: Pubkey, //32
+    /// Spots converted into whitelist tokens and not redeemed back yet.
+    pub tokenized_spots: u64, //8
+}
+
+impl WalletWhitelist {
+    pub fn new(
+        tars_creator: Pubkey,
+        whitelisted_address: Pubkey,
+        whitelist_type: WLType,
+        whitelist_config: &WhitelistConfig,
+    ) -> Result<Self> {
+        // Make sure the tier is scheduled, prices and times are read from it at mint.
+        whitelist_config.whitelist_schedule.tier(whitelist_type)?;
+        Ok(WalletWhitelist {
+            tars_creator,
+            whitelisted_address,
+            whitelist_type,
+            number_of_whitelist_spots_per_user: whitelist_type.spots(),
+            discounted_mint_price: None,
+            whitelist_mint_start_time: None,
+            funder: tars_creator,
+            tokenized_spots: 0,
+        })
+    }
+
+    pub fn mint_price(&self, tier: &WhitelistTierConfig) -> u64 {
+        self.discounted_mint_price
+            .unwrap_or(tier.discounted_mint_price)
+    }
+
+    pub fn mint_start_time(&self, tier: &WhitelistTierConfig) -> u64 {
+        self.whitelist_mint_start_time
+            .unwrap_or(tier.whitelist_mint_start_time)
+    }
+}
+
+/// A single wallet to whitelist in a batch.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct WhitelistEntry {
+    pub whitelisted_address: Pubkey,
+    pub whitelist_type: WLType,
 }
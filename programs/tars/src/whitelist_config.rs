@@ -1,28 +1,38 @@
 use crate::wallet_whitelist::WLType;
+use crate::TarsError;
 use anchor_lang::prelude::*;
+use proc_macros::MaxSerializedSize;
 
-//#[proc_macros::assert_size(128)]
+#[proc_macros::assert_size(borsh = 168)]
 #[repr(C)]
 #[account]
-#[derive(Debug)]
+#[derive(Debug, MaxSerializedSize)]
 pub struct WhitelistConfig {
     pub whitelist_schedule: WhitelistSchedule,
     pub tars_creator: Pubkey, //32
 }
 
-//#[proc_macros::assert_size(32)]
+#[proc_macros::assert_size(borsh = 34)]
 #[repr(C)]
-#[derive(Debug, Clone, Copy, AnchorDeserialize, AnchorSerialize, PartialEq, PartialOrd)]
+#[derive(
+    Debug, Clone, Copy, AnchorDeserialize, AnchorSerialize, PartialEq, PartialOrd, MaxSerializedSize,
+)]
 pub struct WhitelistTierConfig {
     pub whitelist_type: WLType,
     pub number_of_whitelist_spots_total: u64,
     pub discounted_mint_price: u64,
     pub whitelist_mint_start_time: u64,
+    pub whitelist_mint_end_time: u64,
+    /// If true, spots left unused when the tier ends can still be minted at the
+    /// public price once the public sale is live, otherwise they expire.
+    pub unused_spots_to_public: bool,
 }
 
-//#[proc_macros::assert_size(96)]
+#[proc_macros::assert_size(borsh = 136)]
 #[repr(C)]
-#[derive(Debug, Clone, Copy, AnchorDeserialize, AnchorSerialize, PartialEq, PartialOrd)]
+#[derive(
+    Debug, Clone, Copy, AnchorDeserialize, AnchorSerialize, PartialEq, PartialOrd, MaxSerializedSize,
+)]
 pub struct WhitelistSchedule {
     pub wl_start_time_4: WhitelistTierConfig,
     pub wl_start_time_3: WhitelistTierConfig,
@@ -30,7 +40,33 @@ pub struct WhitelistSchedule {
     pub wl_start_time_1: WhitelistTierConfig,
 }
 impl WhitelistSchedule {
+    pub fn tier(&self, whitelist_type: WLType) -> Result<&WhitelistTierConfig> {
+        match whitelist_type {
+            WLType::One => Ok(&self.wl_start_time_1),
+            WLType::Two => Ok(&self.wl_start_time_2),
+            WLType::Three => Ok(&self.wl_start_time_3),
+            WLType::Four => Ok(&self.wl_start_time_4),
+            WLType::Null => err!(TarsError::InvalidWLType),
+        }
+    }
+
+    /// Tier types follow from the position of the tier in the schedule.
+    pub fn set_tier_types(&mut self) {
+        self.wl_start_time_4.whitelist_type = WLType::Four;
+        self.wl_start_time_3.whitelist_type = WLType::Three;
+        self.wl_start_time_2.whitelist_type = WLType::Two;
+        self.wl_start_time_1.whitelist_type = WLType::One;
+    }
+
     pub fn verify_schedule_invariants(&self) {
+        for tier in [
+            &self.wl_start_time_4,
+            &self.wl_start_time_3,
+            &self.wl_start_time_2,
+            &self.wl_start_time_1,
+        ] {
+            assert!(tier.whitelist_mint_end_time >= tier.whitelist_mint_start_time);
+        }
         assert!(
             self.wl_start_time_1.whitelist_mint_start_time
                 >= self.wl_start_time_2.whitelist_mint_start_time
@@ -0,0 +1,156 @@
+use crate::wallet_whitelist::WLType;
+use crate::whitelist_config::*;
+use crate::TarsError;
+use anchor_lang::{prelude::*, Discriminator};
+use solana_program::{program::invoke, system_instruction};
+
+/// Rewrite a `WhitelistConfig` created before tier end times into the current
+/// layout. Anyone can call this, the result only depends on the account.
+#[derive(Accounts)]
+pub struct MigrateWhitelistConfig<'info> {
+    /// CHECK: layout checked in handler
+    #[account(mut, owner = crate::id())]
+    whitelist_config: UncheckedAccount<'info>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+/// `WhitelistConfig` as deployed before tier end times.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct BaselineWhitelistConfig {
+    whitelist_schedule: BaselineWhitelistSchedule,
+    tars_creator: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct BaselineWhitelistSchedule {
+    wl_start_time_4: BaselineWhitelistTierConfig,
+    wl_start_time_3: BaselineWhitelistTierConfig,
+    wl_start_time_2: BaselineWhitelistTierConfig,
+    wl_start_time_1: BaselineWhitelistTierConfig,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct BaselineWhitelistTierConfig {
+    whitelist_type: WLType,
+    number_of_whitelist_spots_total: u64,
+    discounted_mint_price: u64,
+    whitelist_mint_start_time: u64,
+}
+
+impl From<BaselineWhitelistTierConfig> for WhitelistTierConfig {
+    fn from(tier: BaselineWhitelistTierConfig) -> Self {
+        WhitelistTierConfig {
+            whitelist_type: tier.whitelist_type,
+            number_of_whitelist_spots_total: tier.number_of_whitelist_spots_total,
+            discounted_mint_price: tier.discounted_mint_price,
+            whitelist_mint_start_time: tier.whitelist_mint_start_time,
+            // Tiers used to never end, times are compared as i64
+            whitelist_mint_end_time: i64::MAX as u64,
+            unused_spots_to_public: false,
+        }
+    }
+}
+
+impl From<BaselineWhitelistConfig> for WhitelistConfig {
+    fn from(whitelist_config: BaselineWhitelistConfig) -> Self {
+        let schedule = whitelist_config.whitelist_schedule;
+        WhitelistConfig {
+            whitelist_schedule: WhitelistSchedule {
+                wl_start_time_4: schedule.wl_start_time_4.into(),
+                wl_start_time_3: schedule.wl_start_time_3.into(),
+                wl_start_time_2: schedule.wl_start_time_2.into(),
+                wl_start_time_1: schedule.wl_start_time_1.into(),
+            },
+            tars_creator: whitelist_config.tars_creator,
+        }
+    }
+}
+
+pub fn handle_migrate_whitelist_config(ctx: Context<MigrateWhitelistConfig>) -> Result<()> {
+    let whitelist_config_info = ctx.accounts.whitelist_config.to_account_info();
+    let new_len = 8 + WhitelistConfig::MAX_SERIALIZED_SIZE;
+
+    let whitelist_config: WhitelistConfig = {
+        let data = whitelist_config_info.data.borrow();
+        if data.len() < 8 || data[..8] != WhitelistConfig::discriminator() {
+            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+        }
+        // Accounts of the current layout are always created at its full size
+        if data.len() >= new_len {
+            return err!(TarsError::WhitelistAlreadyMigrated);
+        }
+        BaselineWhitelistConfig::deserialize(&mut &data[8..])?.into()
+    };
+
+    let rent_exempt_balance = Rent::get()?.minimum_balance(new_len);
+    if whitelist_config_info.lamports() < rent_exempt_balance {
+        invoke(
+            &system_instruction::transfer(
+                &ctx.accounts.payer.key(),
+                whitelist_config_info.key,
+                rent_exempt_balance - whitelist_config_info.lamports(),
+            ),
+            &[
+                ctx.accounts.payer.to_account_info(),
+                whitelist_config_info.clone(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+    }
+    whitelist_config_info.realloc(new_len, false)?;
+
+    let mut data_ref: &mut [u8] = &mut whitelist_config_info.try_borrow_mut_data()?;
+    whitelist_config.try_serialize(&mut data_ref)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn baseline_tier(whitelist_type: WLType) -> BaselineWhitelistTierConfig {
+        BaselineWhitelistTierConfig {
+            whitelist_type,
+            number_of_whitelist_spots_total: 100,
+            discounted_mint_price: 500_000,
+            whitelist_mint_start_time: 1_650_000_000,
+        }
+    }
+
+    #[test]
+    fn migrates_baseline_whitelist_config() {
+        let baseline = BaselineWhitelistConfig {
+            whitelist_schedule: BaselineWhitelistSchedule {
+                wl_start_time_4: baseline_tier(WLType::Four),
+                wl_start_time_3: baseline_tier(WLType::Three),
+                wl_start_time_2: baseline_tier(WLType::Two),
+                wl_start_time_1: baseline_tier(WLType::One),
+            },
+            tars_creator: Pubkey::new_unique(),
+        };
+        let mut data = WhitelistConfig::discriminator().to_vec();
+        data.append(&mut baseline.try_to_vec().unwrap());
+        // Baseline accounts were sized with `std::mem::size_of`
+        data.resize(168, 0);
+        assert!(data.len() < 8 + WhitelistConfig::MAX_SERIALIZED_SIZE);
+
+        let whitelist_config: WhitelistConfig =
+            BaselineWhitelistConfig::deserialize(&mut &data[8..])
+                .unwrap()
+                .into();
+        let tier = whitelist_config
+            .whitelist_schedule
+            .tier(WLType::Three)
+            .unwrap();
+        assert_eq!(tier.whitelist_type, WLType::Three);
+        assert_eq!(tier.discounted_mint_price, 500_000);
+        assert!(!tier.unused_spots_to_public);
+        // Never ends when compared against the clock
+        assert_eq!(tier.whitelist_mint_end_time as i64, i64::MAX);
+        whitelist_config
+            .whitelist_schedule
+            .verify_schedule_invariants();
+    }
+}
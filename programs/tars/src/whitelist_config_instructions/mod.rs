@@ -1,7 +1,9 @@
 pub mod create_whitelist_config;
 pub mod delete_whitelist_config;
+pub mod migrate_whitelist_config;
 pub mod update_whitelist_config;
 
 pub use create_whitelist_config::*;
 pub use delete_whitelist_config::*;
+pub use migrate_whitelist_config::*;
 pub use update_whitelist_config::*;
@@ -0,0 +1,13 @@
+use anchor_lang::prelude::*;
+use proc_macros::MaxSerializedSize;
+
+/// Lets `delegate` spend up to `number_of_delegated_spots` of a `WalletWhitelist`
+/// in `wl_mint_nft` on behalf of the whitelisted address.
+#[account]
+#[derive(Debug, MaxSerializedSize)]
+pub struct WhitelistDelegation {
+    pub wallet_whitelist: Pubkey,       //32
+    pub whitelisted_address: Pubkey,    //32
+    pub delegate: Pubkey,               //32
+    pub number_of_delegated_spots: u64, //8
+}
@@ -0,0 +1,63 @@
+use crate::constants::WHITELIST_CLEANUP_TIP;
+use crate::wallet_whitelist::*;
+use crate::whitelist_config::*;
+use crate::{cmp_pubkeys, TarsError};
+use anchor_lang::prelude::*;
+use common::close_account;
+
+/// Close a `WalletWhitelist` that can no longer be used. Anyone can call this,
+/// the rent goes back to the funder minus a small tip for the caller. Accounts
+/// created before the funder was recorded go through `migrate_wallet_whitelist`
+/// first, which makes the tars creator who paid for them their funder.
+#[derive(Accounts)]
+pub struct CloseExpiredWhitelistAccount<'info> {
+    #[account(mut, has_one = funder)]
+    wallet_whitelist: Account<'info, WalletWhitelist>,
+    #[account(constraint = cmp_pubkeys(&whitelist_config.tars_creator, &wallet_whitelist.tars_creator) @ TarsError::PublicKeyMismatch)]
+    whitelist_config: Account<'info, WhitelistConfig>,
+    /// CHECK: only receives the rent of the closed account
+    #[account(mut)]
+    funder: UncheckedAccount<'info>,
+    #[account(mut)]
+    cranker: Signer<'info>,
+}
+
+pub fn handle_close_expired_whitelist_account(
+    ctx: Context<CloseExpiredWhitelistAccount>,
+) -> Result<()> {
+    let wallet_whitelist = &ctx.accounts.wallet_whitelist;
+    let tier = ctx
+        .accounts
+        .whitelist_config
+        .whitelist_schedule
+        .tier(wallet_whitelist.whitelist_type)?;
+    let clock = Clock::get()?;
+
+    // Tokenized spots can still be redeemed back into the account.
+    let spots_used_up = wallet_whitelist.number_of_whitelist_spots_per_user == 0
+        && wallet_whitelist.tokenized_spots == 0;
+    // Spots that fall through to the public sale stay usable after the tier ends.
+    let tier_expired =
+        !tier.unused_spots_to_public && clock.unix_timestamp > tier.whitelist_mint_end_time as i64;
+    if !spots_used_up && !tier_expired {
+        return err!(TarsError::WhitelistAccountStillActive);
+    }
+
+    let mut wallet_whitelist_info = wallet_whitelist.to_account_info();
+    let cranker = &ctx.accounts.cranker;
+    let tip = WHITELIST_CLEANUP_TIP.min(wallet_whitelist_info.lamports());
+    **wallet_whitelist_info.lamports.borrow_mut() = wallet_whitelist_info
+        .lamports()
+        .checked_sub(tip)
+        .ok_or(TarsError::NumericalOverflowError)?;
+    **cranker.lamports.borrow_mut() = cranker
+        .lamports()
+        .checked_add(tip)
+        .ok_or(TarsError::NumericalOverflowError)?;
+
+    close_account(
+        &mut wallet_whitelist_info,
+        &mut ctx.accounts.funder.to_account_info(),
+    )?;
+    Ok(())
+}
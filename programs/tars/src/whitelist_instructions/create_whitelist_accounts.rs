@@ -0,0 +1,71 @@
+use crate::constants::TARS_CREATOR_WALLET;
+use crate::wallet_whitelist::*;
+use crate::whitelist_config::*;
+use crate::{cmp_pubkeys, TarsError};
+use anchor_lang::prelude::*;
+use mpl_token_metadata::utils::create_or_allocate_account_raw;
+use std::str::FromStr;
+
+/// Create a `WalletWhitelist` account for every entry in a batch.
+#[derive(Accounts)]
+pub struct CreateWhitelistAccounts<'info> {
+    #[account(has_one = tars_creator)]
+    whitelist_config: Account<'info, WhitelistConfig>,
+    #[account(mut, address = Pubkey::from_str(TARS_CREATOR_WALLET).unwrap())]
+    tars_creator: Signer<'info>,
+    system_program: Program<'info, System>,
+    rent: Sysvar<'info, Rent>,
+    // > One per entry, in the same order as the entries
+    // wallet_whitelist
+}
+
+pub fn handle_create_whitelist_accounts<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateWhitelistAccounts<'info>>,
+    entries: Vec<WhitelistEntry>,
+) -> Result<()> {
+    if ctx.remaining_accounts.len() != entries.len() {
+        return err!(TarsError::WhitelistEntriesMismatch);
+    }
+    let tars_creator = &ctx.accounts.tars_creator;
+    let tars_creator_key = tars_creator.key();
+
+    for (entry, wallet_whitelist_info) in entries.iter().zip(ctx.remaining_accounts.iter()) {
+        let seeds = [
+            b"wallet-whitelist".as_ref(),
+            entry.whitelisted_address.as_ref(),
+            tars_creator_key.as_ref(),
+        ];
+        let (wallet_whitelist_key, bump) = Pubkey::find_program_address(&seeds, &crate::id());
+        if !cmp_pubkeys(&wallet_whitelist_key, wallet_whitelist_info.key) {
+            return err!(TarsError::DerivedKeyInvalid);
+        }
+        if !wallet_whitelist_info.data_is_empty() {
+            return err!(TarsError::WalletAlreadyWhitelisted);
+        }
+
+        create_or_allocate_account_raw(
+            crate::id(),
+            wallet_whitelist_info,
+            &ctx.accounts.rent.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &tars_creator.to_account_info(),
+            8 + WalletWhitelist::MAX_SERIALIZED_SIZE,
+            &[
+                b"wallet-whitelist".as_ref(),
+                entry.whitelisted_address.as_ref(),
+                tars_creator_key.as_ref(),
+                &[bump],
+            ],
+        )?;
+
+        let wallet_whitelist = WalletWhitelist::new(
+            tars_creator_key,
+            entry.whitelisted_address,
+            entry.whitelist_type,
+            &ctx.accounts.whitelist_config,
+        )?;
+        let mut data_ref: &mut [u8] = &mut wallet_whitelist_info.try_borrow_mut_data()?;
+        wallet_whitelist.try_serialize(&mut data_ref)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token};
+
+use crate::{Tars, TarsError};
+
+/// Create the whitelist token mint of the tars and set it as its
+/// `whitelist_mint_settings.mint`. Being created here with the whitelist mint
+/// authority PDA, its whole supply comes from `tokenize_whitelist_spots`.
+#[derive(Accounts)]
+pub struct CreateWhitelistTokenMint<'info> {
+    #[account(mut, has_one = authority)]
+    tars: Box<Account<'info, Tars>>,
+    authority: Signer<'info>,
+    #[account(init,
+        payer = payer,
+        seeds = [b"whitelist-mint".as_ref(), tars.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = whitelist_mint_authority
+    )]
+    whitelist_token_mint: Account<'info, Mint>,
+    /// CHECK: account constraints checked in account trait
+    #[account(seeds = [b"whitelist-mint-authority".as_ref(), tars.key().as_ref()], bump)]
+    whitelist_mint_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    token_program: Program<'info, Token>,
+    system_program: Program<'info, System>,
+    rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_create_whitelist_token_mint(ctx: Context<CreateWhitelistTokenMint>) -> Result<()> {
+    let mint = ctx.accounts.whitelist_token_mint.key();
+    let ws = ctx
+        .accounts
+        .tars
+        .data
+        .whitelist_mint_settings
+        .as_mut()
+        .ok_or(TarsError::NoWhitelistMintSettings)?;
+    ws.mint = mint;
+    Ok(())
+}
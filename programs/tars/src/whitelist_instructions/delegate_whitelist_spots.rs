@@ -0,0 +1,40 @@
+use crate::wallet_whitelist::*;
+use crate::whitelist_delegation::*;
+use crate::TarsError;
+use anchor_lang::prelude::*;
+
+/// Delegate some of a wallet's whitelist spots to another signer.
+#[derive(Accounts)]
+pub struct DelegateWhitelistSpots<'info> {
+    #[account(init,
+        payer = whitelisted_address,
+        space = 8 + WhitelistDelegation::MAX_SERIALIZED_SIZE,
+        seeds = [b"whitelist-delegation".as_ref(), wallet_whitelist.key().as_ref()],
+        bump
+    )]
+    whitelist_delegation: Account<'info, WhitelistDelegation>,
+    #[account(has_one = whitelisted_address)]
+    wallet_whitelist: Account<'info, WalletWhitelist>,
+    #[account(mut)]
+    whitelisted_address: Signer<'info>,
+    /// CHECK: delegate can be any account and is not written to or read
+    delegate: UncheckedAccount<'info>,
+    system_program: Program<'info, System>,
+}
+
+pub fn handle_delegate_whitelist_spots(
+    ctx: Context<DelegateWhitelistSpots>,
+    number_of_delegated_spots: u64,
+) -> Result<()> {
+    let wallet_whitelist = &ctx.accounts.wallet_whitelist;
+    if number_of_delegated_spots > wallet_whitelist.number_of_whitelist_spots_per_user {
+        return err!(TarsError::InvalidNumberofWL);
+    }
+
+    let whitelist_delegation = &mut ctx.accounts.whitelist_delegation;
+    whitelist_delegation.wallet_whitelist = wallet_whitelist.key();
+    whitelist_delegation.whitelisted_address = ctx.accounts.whitelisted_address.key();
+    whitelist_delegation.delegate = ctx.accounts.delegate.key();
+    whitelist_delegation.number_of_delegated_spots = number_of_delegated_spots;
+    Ok(())
+}
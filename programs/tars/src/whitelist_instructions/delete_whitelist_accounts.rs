@@ -0,0 +1,49 @@
+use crate::wallet_whitelist::*;
+use crate::{cmp_pubkeys, TarsError};
+use anchor_lang::prelude::*;
+use common::close_account;
+
+/// Close the `WalletWhitelist` accounts of a batch of wallets.
+#[derive(Accounts)]
+pub struct DeleteWhitelistAccounts<'info> {
+    #[account(mut)]
+    tars_creator: Signer<'info>,
+    // > One per whitelisted address, in the same order as the addresses
+    // wallet_whitelist
+}
+
+pub fn handle_delete_whitelist_accounts<'info>(
+    ctx: Context<'_, '_, '_, 'info, DeleteWhitelistAccounts<'info>>,
+    whitelisted_addresses: Vec<Pubkey>,
+) -> Result<()> {
+    if ctx.remaining_accounts.len() != whitelisted_addresses.len() {
+        return err!(TarsError::WhitelistEntriesMismatch);
+    }
+    let tars_creator_key = ctx.accounts.tars_creator.key();
+
+    for (whitelisted_address, wallet_whitelist_info) in whitelisted_addresses
+        .iter()
+        .zip(ctx.remaining_accounts.iter())
+    {
+        let seeds = [
+            b"wallet-whitelist".as_ref(),
+            whitelisted_address.as_ref(),
+            tars_creator_key.as_ref(),
+        ];
+        let (wallet_whitelist_key, _) = Pubkey::find_program_address(&seeds, &crate::id());
+        if !cmp_pubkeys(&wallet_whitelist_key, wallet_whitelist_info.key) {
+            return err!(TarsError::DerivedKeyInvalid);
+        }
+
+        let wallet_whitelist: Account<WalletWhitelist> = Account::try_from(wallet_whitelist_info)?;
+        if !cmp_pubkeys(&wallet_whitelist.tars_creator, &tars_creator_key) {
+            return err!(TarsError::PublicKeyMismatch);
+        }
+
+        close_account(
+            &mut wallet_whitelist_info.clone(),
+            &mut ctx.accounts.tars_creator.to_account_info(),
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use crate::wallet_whitelist::*;
+use crate::TarsError;
+use anchor_lang::{prelude::*, Discriminator};
+use solana_program::{program::invoke, system_instruction};
+
+/// Rewrite a `WalletWhitelist` created before the per-wallet overrides, the
+/// funder and the tokenized spots were added into the current layout. Anyone can call this, the result
+/// only depends on the account.
+#[derive(Accounts)]
+pub struct MigrateWalletWhitelist<'info> {
+    /// CHECK: layout checked in handler
+    #[account(mut, owner = crate::id())]
+    wallet_whitelist: UncheckedAccount<'info>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+/// `WalletWhitelist` as deployed before the overrides, holding copies of the
+/// tier price and start time.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct BaselineWalletWhitelist {
+    tars_creator: Pubkey,
+    whitelisted_address: Pubkey,
+    whitelist_type: WLType,
+    number_of_whitelist_spots_per_user: u64,
+    discounted_mint_price: u64,
+    whitelist_mint_start_time: u64,
+}
+
+impl From<BaselineWalletWhitelist> for WalletWhitelist {
+    fn from(wallet_whitelist: BaselineWalletWhitelist) -> Self {
+        WalletWhitelist {
+            tars_creator: wallet_whitelist.tars_creator,
+            whitelisted_address: wallet_whitelist.whitelisted_address,
+            whitelist_type: wallet_whitelist.whitelist_type,
+            number_of_whitelist_spots_per_user: wallet_whitelist.number_of_whitelist_spots_per_user,
+            // The copies were taken from the tier, which is now read live instead
+            discounted_mint_price: None,
+            whitelist_mint_start_time: None,
+            // Only the tars creator could create these accounts
+            funder: wallet_whitelist.tars_creator,
+            // Spots could only be tokenized on the current layout
+            tokenized_spots: 0,
+        }
+    }
+}
+
+pub fn handle_migrate_wallet_whitelist(ctx: Context<MigrateWalletWhitelist>) -> Result<()> {
+    let wallet_whitelist_info = ctx.accounts.wallet_whitelist.to_account_info();
+    let new_len = 8 + WalletWhitelist::MAX_SERIALIZED_SIZE;
+
+    let wallet_whitelist: WalletWhitelist = {
+        let data = wallet_whitelist_info.data.borrow();
+        if data.len() < 8 || data[..8] != WalletWhitelist::discriminator() {
+            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+        }
+        // Accounts of the current layout are always created at its full size
+        if data.len() >= new_len {
+            return err!(TarsError::WhitelistAlreadyMigrated);
+        }
+        BaselineWalletWhitelist::deserialize(&mut &data[8..])?.into()
+    };
+
+    let rent_exempt_balance = Rent::get()?.minimum_balance(new_len);
+    if wallet_whitelist_info.lamports() < rent_exempt_balance {
+        invoke(
+            &system_instruction::transfer(
+                &ctx.accounts.payer.key(),
+                wallet_whitelist_info.key,
+                rent_exempt_balance - wallet_whitelist_info.lamports(),
+            ),
+            &[
+                ctx.accounts.payer.to_account_info(),
+                wallet_whitelist_info.clone(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+    }
+    wallet_whitelist_info.realloc(new_len, false)?;
+
+    let mut data_ref: &mut [u8] = &mut wallet_whitelist_info.try_borrow_mut_data()?;
+    wallet_whitelist.try_serialize(&mut data_ref)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_baseline_wallet_whitelist() {
+        let tars_creator = Pubkey::new_unique();
+        let baseline = BaselineWalletWhitelist {
+            tars_creator,
+            whitelisted_address: Pubkey::new_unique(),
+            whitelist_type: WLType::Two,
+            number_of_whitelist_spots_per_user: 2,
+            discounted_mint_price: 500_000,
+            whitelist_mint_start_time: 1_650_000_000,
+        };
+        let mut data = WalletWhitelist::discriminator().to_vec();
+        data.append(&mut baseline.try_to_vec().unwrap());
+        // Baseline accounts were sized with `std::mem::size_of`
+        data.resize(104, 0);
+
+        let wallet_whitelist: WalletWhitelist =
+            BaselineWalletWhitelist::deserialize(&mut &data[8..])
+                .unwrap()
+                .into();
+        assert_eq!(wallet_whitelist.whitelist_type, WLType::Two);
+        assert_eq!(wallet_whitelist.number_of_whitelist_spots_per_user, 2);
+        assert_eq!(wallet_whitelist.discounted_mint_price, None);
+        assert_eq!(wallet_whitelist.whitelist_mint_start_time, None);
+        assert_eq!(wallet_whitelist.funder, tars_creator);
+        assert_eq!(wallet_whitelist.tokenized_spots, 0);
+        assert!(data.len() < 8 + WalletWhitelist::MAX_SERIALIZED_SIZE);
+    }
+}
@@ -1,5 +1,25 @@
+pub mod close_expired_whitelist_account;
 pub mod create_whitelist_account;
+pub mod create_whitelist_accounts;
+pub mod create_whitelist_token_mint;
+pub mod delegate_whitelist_spots;
 pub mod delete_whitelist_account;
+pub mod delete_whitelist_accounts;
+pub mod migrate_wallet_whitelist;
+pub mod redeem_whitelist_tokens;
+pub mod revoke_whitelist_delegation;
+pub mod tokenize_whitelist_spots;
+pub mod update_whitelist_account;
 
+pub use close_expired_whitelist_account::*;
 pub use create_whitelist_account::*;
+pub use create_whitelist_accounts::*;
+pub use create_whitelist_token_mint::*;
+pub use delegate_whitelist_spots::*;
 pub use delete_whitelist_account::*;
+pub use delete_whitelist_accounts::*;
+pub use migrate_wallet_whitelist::*;
+pub use redeem_whitelist_tokens::*;
+pub use revoke_whitelist_delegation::*;
+pub use tokenize_whitelist_spots::*;
+pub use update_whitelist_account::*;
@@ -0,0 +1,60 @@
+use crate::wallet_whitelist::*;
+use crate::{assert_is_ata, assert_keys_equal, spl_token_burn, Tars, TarsError, TokenBurnParams};
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use common::*;
+
+/// Burn whitelist tokens of the tars' `whitelist_mint_settings.mint` back into
+/// whitelist spots, the reverse of `tokenize_whitelist_spots`. Only the whitelist
+/// token mint of the tars is accepted, as its tokens all come from spots.
+#[derive(Accounts)]
+pub struct RedeemWhitelistTokens<'info> {
+    tars: Box<Account<'info, Tars>>,
+    #[account(mut, has_one = whitelisted_address)]
+    wallet_whitelist: Account<'info, WalletWhitelist>,
+    whitelisted_address: Signer<'info>,
+    /// CHECK: account checked in CPI
+    #[account(mut, seeds = [b"whitelist-mint".as_ref(), tars.key().as_ref()], bump)]
+    whitelist_token_mint: UncheckedAccount<'info>,
+    /// CHECK: account checked in handler
+    #[account(mut)]
+    whitelist_token_account: UncheckedAccount<'info>,
+    token_program: Program<'info, Token>,
+}
+
+pub fn handle_redeem_whitelist_tokens(
+    ctx: Context<RedeemWhitelistTokens>,
+    amount: u64,
+) -> Result<()> {
+    let ws = ctx
+        .accounts
+        .tars
+        .data
+        .whitelist_mint_settings
+        .as_ref()
+        .ok_or(TarsError::NoWhitelistMintSettings)?;
+    assert_keys_equal(&ctx.accounts.whitelist_token_mint.key(), &ws.mint)?;
+    assert_is_ata(
+        &ctx.accounts.whitelist_token_account,
+        &ctx.accounts.whitelisted_address.key(),
+        &ws.mint,
+    )?;
+
+    spl_token_burn(TokenBurnParams {
+        mint: ctx.accounts.whitelist_token_mint.to_account_info(),
+        source: ctx.accounts.whitelist_token_account.to_account_info(),
+        amount,
+        authority: ctx.accounts.whitelisted_address.to_account_info(),
+        authority_signer_seeds: None,
+        token_program: ctx.accounts.token_program.to_account_info(),
+    })?;
+
+    let wallet_whitelist = &mut ctx.accounts.wallet_whitelist;
+    wallet_whitelist
+        .number_of_whitelist_spots_per_user
+        .try_add_assign(amount)?;
+    // Tokens can be redeemed into another whitelisted wallet than the one
+    // they were tokenized from.
+    wallet_whitelist.tokenized_spots = wallet_whitelist.tokenized_spots.saturating_sub(amount);
+    Ok(())
+}
@@ -0,0 +1,15 @@
+use crate::whitelist_delegation::*;
+use anchor_lang::prelude::*;
+
+/// Revoke a delegation and return its rent to the whitelisted address.
+#[derive(Accounts)]
+pub struct RevokeWhitelistDelegation<'info> {
+    #[account(mut, has_one = whitelisted_address, close = whitelisted_address)]
+    whitelist_delegation: Account<'info, WhitelistDelegation>,
+    #[account(mut)]
+    whitelisted_address: Signer<'info>,
+}
+
+pub fn handle_revoke_whitelist_delegation(_ctx: Context<RevokeWhitelistDelegation>) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use crate::wallet_whitelist::*;
+use crate::{
+    assert_is_ata, assert_keys_equal, spl_token_mint_to, Tars, TarsError, TokenMintToParams,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use common::*;
+
+/// Convert whitelist spots into whitelist tokens of the tars' `whitelist_mint_settings.mint`,
+/// which must be the whitelist token mint created by `create_whitelist_token_mint`.
+#[derive(Accounts)]
+pub struct TokenizeWhitelistSpots<'info> {
+    tars: Box<Account<'info, Tars>>,
+    #[account(mut, has_one = whitelisted_address)]
+    wallet_whitelist: Account<'info, WalletWhitelist>,
+    whitelisted_address: Signer<'info>,
+    /// CHECK: account checked in CPI
+    #[account(mut, seeds = [b"whitelist-mint".as_ref(), tars.key().as_ref()], bump)]
+    whitelist_token_mint: UncheckedAccount<'info>,
+    /// CHECK: account checked in handler
+    #[account(mut)]
+    whitelist_token_account: UncheckedAccount<'info>,
+    /// CHECK: account constraints checked in account trait
+    #[account(seeds = [b"whitelist-mint-authority".as_ref(), tars.key().as_ref()], bump)]
+    whitelist_mint_authority: UncheckedAccount<'info>,
+    token_program: Program<'info, Token>,
+}
+
+pub fn handle_tokenize_whitelist_spots(
+    ctx: Context<TokenizeWhitelistSpots>,
+    amount: u64,
+) -> Result<()> {
+    let tars = &ctx.accounts.tars;
+    let ws = tars
+        .data
+        .whitelist_mint_settings
+        .as_ref()
+        .ok_or(TarsError::NoWhitelistMintSettings)?;
+    assert_keys_equal(&ctx.accounts.whitelist_token_mint.key(), &ws.mint)?;
+    assert_is_ata(
+        &ctx.accounts.whitelist_token_account,
+        &ctx.accounts.whitelisted_address.key(),
+        &ws.mint,
+    )?;
+
+    let wallet_whitelist = &mut ctx.accounts.wallet_whitelist;
+    if amount > wallet_whitelist.number_of_whitelist_spots_per_user {
+        return err!(TarsError::InvalidNumberofWL);
+    }
+    wallet_whitelist
+        .number_of_whitelist_spots_per_user
+        .try_sub_assign(amount)?;
+    wallet_whitelist.tokenized_spots.try_add_assign(amount)?;
+
+    let tars_key = tars.key();
+    let authority_seeds = [
+        b"whitelist-mint-authority".as_ref(),
+        tars_key.as_ref(),
+        &[*ctx.bumps.get("whitelist_mint_authority").unwrap()],
+    ];
+    spl_token_mint_to(TokenMintToParams {
+        mint: ctx.accounts.whitelist_token_mint.to_account_info(),
+        destination: ctx.accounts.whitelist_token_account.to_account_info(),
+        amount,
+        authority: ctx.accounts.whitelist_mint_authority.to_account_info(),
+        authority_signer_seeds: &authority_seeds,
+        token_program: ctx.accounts.token_program.to_account_info(),
+    })?;
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use crate::wallet_whitelist::*;
+use anchor_lang::prelude::*;
+
+/// Set or clear the per-wallet overrides of the tier's price and start time.
+#[derive(Accounts)]
+pub struct UpdateWhitelistAccount<'info> {
+    #[account(mut, has_one = tars_creator)]
+    wallet_whitelist: Account<'info, WalletWhitelist>,
+    tars_creator: Signer<'info>,
+}
+
+pub fn handle_update_whitelist_account(
+    ctx: Context<UpdateWhitelistAccount>,
+    discounted_mint_price: Option<u64>,
+    whitelist_mint_start_time: Option<u64>,
+) -> Result<()> {
+    let wallet_whitelist = &mut ctx.accounts.wallet_whitelist;
+    wallet_whitelist.discounted_mint_price = discounted_mint_price;
+    wallet_whitelist.whitelist_mint_start_time = whitelist_mint_start_time;
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use crate::wallet_whitelist::WLType;
+use crate::TarsError;
+use anchor_lang::prelude::*;
+use proc_macros::MaxSerializedSize;
+use solana_program::hash::hashv;
+
+/// Number of Feistel rounds used to shuffle the registrations.
+const SHUFFLE_ROUNDS: u8 = 4;
+/// Slots between closing registration and the slot whose hash seeds the draw.
+pub const DRAW_SLOT_DELAY: u64 = 32;
+
+#[account]
+#[derive(Debug, MaxSerializedSize)]
+pub struct WhitelistRaffle {
+    pub tars: Pubkey,                 //32
+    pub tars_creator: Pubkey,         //32
+    pub whitelist_type: WLType,       //1
+    pub registration_start_time: u64, //8
+    pub registration_end_time: u64,   //8
+    /// After this, the raffle can be closed with tickets left unsettled.
+    pub settlement_end_time: u64, //8
+    /// Lamports locked in each ticket, returned when the ticket is settled.
+    pub deposit: u64, //8
+    pub number_of_winners: u32,       //4
+    pub number_of_registrations: u32, //4
+    pub number_of_settled_tickets: u32, //4
+    /// Slot whose hash seeds the draw, fixed when registration is closed.
+    pub draw_slot: Option<u64>, //9
+    /// Set from the hash of `draw_slot` when the raffle is drawn.
+    pub seed: Option<[u8; 32]>, //33
+}
+
+#[account]
+#[derive(Debug, MaxSerializedSize)]
+pub struct RaffleTicket {
+    pub raffle: Pubkey,     //32
+    pub registrant: Pubkey, //32
+    pub index: u32,         //4
+}
+
+impl WhitelistRaffle {
+    /// A ticket wins if its position in the seeded shuffle of all registrations
+    /// is below `number_of_winners`. Every ticket can check this on its own.
+    pub fn is_winner(&self, index: u32) -> Result<bool> {
+        let seed = self.seed.as_ref().ok_or(TarsError::RaffleNotDrawn)?;
+        if index >= self.number_of_registrations {
+            return err!(TarsError::IndexGreaterThanLength);
+        }
+        Ok(shuffled_position(seed, index, self.number_of_registrations) < self.number_of_winners)
+    }
+}
+
+/// Hash of the first slot at or after `slot` in the SlotHashes sysvar data.
+/// Entries are sorted newest first and skipped slots have none, so the result
+/// is only known to be the first one once an older entry is seen as well.
+pub fn slot_hash_at_or_after(data: &[u8], slot: u64) -> Option<[u8; 32]> {
+    let len = u64::from_le_bytes(data.get(0..8)?.try_into().ok()?) as usize;
+    let mut found = None;
+    for i in 0..len {
+        let entry = data.get(8 + i * 40..8 + (i + 1) * 40)?;
+        let entry_slot = u64::from_le_bytes(entry[0..8].try_into().unwrap());
+        if entry_slot < slot {
+            return found;
+        }
+        found = Some(entry[8..40].try_into().unwrap());
+        if entry_slot == slot {
+            return found;
+        }
+    }
+    None
+}
+
+/// Position of `index` in a pseudo-random permutation of `0..len`, built from a
+/// Feistel network over the smallest even bit width covering `len` and
+/// cycle-walking back into range.
+pub fn shuffled_position(seed: &[u8; 32], index: u32, len: u32) -> u32 {
+    let mut bits = 32 - len.saturating_sub(1).leading_zeros();
+    bits = bits.max(2);
+    bits += bits % 2;
+    let half = bits / 2;
+    let mask = (1u32 << half) - 1;
+
+    let mut position = index;
+    loop {
+        let mut left = position >> half;
+        let mut right = position & mask;
+        for round in 0..SHUFFLE_ROUNDS {
+            let hash = hashv(&[seed, &[round], &right.to_le_bytes()]);
+            let f = u32::from_le_bytes(hash.to_bytes()[0..4].try_into().unwrap()) & mask;
+            let new_right = left ^ f;
+            left = right;
+            right = new_right;
+        }
+        position = (left << half) | right;
+        if position < len {
+            return position;
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn shuffle_is_a_permutation() {
+        let seed = [7u8; 32];
+        for len in [1u32, 2, 3, 10, 64, 1000] {
+            let mut seen = vec![false; len as usize];
+            for index in 0..len {
+                let position = shuffled_position(&seed, index, len);
+                assert!(position < len);
+                assert!(!seen[position as usize]);
+                seen[position as usize] = true;
+            }
+        }
+    }
+
+    #[test]
+    fn draws_exact_number_of_winners() {
+        let raffle = WhitelistRaffle {
+            tars: Pubkey::new_unique(),
+            tars_creator: Pubkey::new_unique(),
+            whitelist_type: WLType::One,
+            registration_start_time: 0,
+            registration_end_time: 0,
+            settlement_end_time: 0,
+            deposit: 0,
+            number_of_winners: 25,
+            number_of_registrations: 300,
+            number_of_settled_tickets: 0,
+            draw_slot: Some(0),
+            seed: Some([42u8; 32]),
+        };
+        let winners = (0..raffle.number_of_registrations)
+            .filter(|index| raffle.is_winner(*index).unwrap())
+            .count();
+        assert_eq!(winners, 25);
+    }
+
+    fn slot_hashes(slots: &[u64]) -> Vec<u8> {
+        let mut data = (slots.len() as u64).to_le_bytes().to_vec();
+        for slot in slots {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(&[*slot as u8; 32]);
+        }
+        data
+    }
+
+    #[test]
+    fn finds_first_slot_hash_at_or_after() {
+        let data = slot_hashes(&[110, 108, 107, 105]);
+        assert_eq!(slot_hash_at_or_after(&data, 107), Some([107u8; 32]));
+        // 106 was skipped, the next slot with a bank seeds the draw
+        assert_eq!(slot_hash_at_or_after(&data, 106), Some([107u8; 32]));
+        assert_eq!(slot_hash_at_or_after(&data, 111), None);
+        // the entries before 105 are gone, 104 may have had a bank
+        assert_eq!(slot_hash_at_or_after(&data, 104), None);
+        assert_eq!(slot_hash_at_or_after(&data, 105), Some([105u8; 32]));
+    }
+}
@@ -0,0 +1,36 @@
+use crate::whitelist_raffle::*;
+use crate::TarsError;
+use anchor_lang::prelude::*;
+use solana_program::slot_hashes::MAX_ENTRIES;
+
+/// Fix the slot whose hash seeds the raffle once registration has closed.
+/// The slot is still in the future, so nobody knows its hash when it's fixed
+/// and nobody can retry the draw for a different outcome.
+#[derive(Accounts)]
+pub struct CloseWhitelistRaffleRegistration<'info> {
+    #[account(mut)]
+    whitelist_raffle: Account<'info, WhitelistRaffle>,
+}
+
+pub fn handle_close_whitelist_raffle_registration(
+    ctx: Context<CloseWhitelistRaffleRegistration>,
+) -> Result<()> {
+    let whitelist_raffle = &mut ctx.accounts.whitelist_raffle;
+    if whitelist_raffle.seed.is_some() {
+        return err!(TarsError::RaffleAlreadyDrawn);
+    }
+    let clock = Clock::get()?;
+    if clock.unix_timestamp <= whitelist_raffle.registration_end_time as i64 {
+        return err!(TarsError::RaffleRegistrationOpen);
+    }
+    // A new slot can only be fixed once the hash of the old one is gone
+    // from the slot hashes, so the draw was missed rather than avoided.
+    if let Some(draw_slot) = whitelist_raffle.draw_slot {
+        if clock.slot <= draw_slot + MAX_ENTRIES as u64 {
+            return err!(TarsError::RaffleDrawScheduled);
+        }
+    }
+
+    whitelist_raffle.draw_slot = Some(clock.slot + DRAW_SLOT_DELAY);
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use crate::constants::TARS_CREATOR_WALLET;
+use crate::wallet_whitelist::WLType;
+use crate::whitelist_config::*;
+use crate::whitelist_raffle::*;
+use crate::{Tars, TarsError};
+use anchor_lang::prelude::*;
+use std::str::FromStr;
+
+/// Open a raffle for whitelist spots of a tars.
+#[derive(Accounts)]
+pub struct CreateWhitelistRaffle<'info> {
+    #[account(init,
+        payer = tars_creator,
+        space = 8 + WhitelistRaffle::MAX_SERIALIZED_SIZE,
+        seeds = [b"whitelist-raffle".as_ref(), tars.key().as_ref()],
+        bump
+    )]
+    whitelist_raffle: Account<'info, WhitelistRaffle>,
+    tars: Box<Account<'info, Tars>>,
+    #[account(has_one = tars_creator)]
+    whitelist_config: Account<'info, WhitelistConfig>,
+    #[account(mut, address = Pubkey::from_str(TARS_CREATOR_WALLET).unwrap())]
+    tars_creator: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+pub fn handle_create_whitelist_raffle(
+    ctx: Context<CreateWhitelistRaffle>,
+    whitelist_type: WLType,
+    registration_start_time: u64,
+    registration_end_time: u64,
+    settlement_end_time: u64,
+    deposit: u64,
+    number_of_winners: u32,
+) -> Result<()> {
+    ctx.accounts
+        .whitelist_config
+        .whitelist_schedule
+        .tier(whitelist_type)?;
+    if registration_end_time < registration_start_time
+        || settlement_end_time < registration_end_time
+    {
+        return err!(TarsError::InvalidRaffleSchedule);
+    }
+
+    let whitelist_raffle = &mut ctx.accounts.whitelist_raffle;
+    whitelist_raffle.tars = ctx.accounts.tars.key();
+    whitelist_raffle.tars_creator = ctx.accounts.tars_creator.key();
+    whitelist_raffle.whitelist_type = whitelist_type;
+    whitelist_raffle.registration_start_time = registration_start_time;
+    whitelist_raffle.registration_end_time = registration_end_time;
+    whitelist_raffle.settlement_end_time = settlement_end_time;
+    whitelist_raffle.deposit = deposit;
+    whitelist_raffle.number_of_winners = number_of_winners;
+    whitelist_raffle.number_of_registrations = 0;
+    whitelist_raffle.number_of_settled_tickets = 0;
+    whitelist_raffle.draw_slot = None;
+    whitelist_raffle.seed = None;
+    Ok(())
+}
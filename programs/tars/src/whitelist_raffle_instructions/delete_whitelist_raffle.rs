@@ -0,0 +1,24 @@
+use crate::whitelist_raffle::*;
+use crate::TarsError;
+use anchor_lang::prelude::*;
+
+/// Close a raffle once every ticket has been settled or settlement has ended.
+/// Tickets left unsettled get their deposit back with `reclaim_raffle_ticket`.
+#[derive(Accounts)]
+pub struct DeleteWhitelistRaffle<'info> {
+    #[account(mut, has_one = tars_creator, close = tars_creator)]
+    whitelist_raffle: Account<'info, WhitelistRaffle>,
+    #[account(mut)]
+    tars_creator: Signer<'info>,
+}
+
+pub fn handle_delete_whitelist_raffle(ctx: Context<DeleteWhitelistRaffle>) -> Result<()> {
+    let whitelist_raffle = &ctx.accounts.whitelist_raffle;
+    if whitelist_raffle.number_of_registrations > 0
+        && whitelist_raffle.number_of_settled_tickets < whitelist_raffle.number_of_registrations
+        && Clock::get()?.unix_timestamp <= whitelist_raffle.settlement_end_time as i64
+    {
+        return err!(TarsError::RaffleNotSettled);
+    }
+    Ok(())
+}
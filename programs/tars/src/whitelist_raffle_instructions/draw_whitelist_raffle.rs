@@ -0,0 +1,35 @@
+use crate::whitelist_raffle::*;
+use crate::TarsError;
+use anchor_lang::prelude::*;
+use solana_program::{hash::hashv, sysvar::SysvarId};
+
+/// Seed the raffle from the hash of the slot fixed when registration closed.
+/// Anyone can call this, so the outcome doesn't depend on the operator.
+#[derive(Accounts)]
+pub struct DrawWhitelistRaffle<'info> {
+    #[account(mut)]
+    whitelist_raffle: Account<'info, WhitelistRaffle>,
+    /// CHECK: account constraints checked in account trait
+    #[account(address = SlotHashes::id())]
+    recent_slothashes: UncheckedAccount<'info>,
+}
+
+pub fn handle_draw_whitelist_raffle(ctx: Context<DrawWhitelistRaffle>) -> Result<()> {
+    let whitelist_raffle = &mut ctx.accounts.whitelist_raffle;
+    if whitelist_raffle.seed.is_some() {
+        return err!(TarsError::RaffleAlreadyDrawn);
+    }
+    let draw_slot = whitelist_raffle
+        .draw_slot
+        .ok_or(TarsError::RaffleDrawNotScheduled)?;
+    if Clock::get()?.slot <= draw_slot {
+        return err!(TarsError::RaffleDrawSlotPending);
+    }
+
+    let data = ctx.accounts.recent_slothashes.data.borrow();
+    let slot_hash =
+        slot_hash_at_or_after(&data, draw_slot).ok_or(TarsError::RaffleDrawSlotHashMissing)?;
+    let seed = hashv(&[&slot_hash, whitelist_raffle.key().as_ref()]);
+    whitelist_raffle.seed = Some(seed.to_bytes());
+    Ok(())
+}
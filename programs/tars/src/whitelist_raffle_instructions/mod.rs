@@ -0,0 +1,15 @@
+pub mod close_whitelist_raffle_registration;
+pub mod create_whitelist_raffle;
+pub mod delete_whitelist_raffle;
+pub mod draw_whitelist_raffle;
+pub mod reclaim_raffle_ticket;
+pub mod register_for_whitelist_raffle;
+pub mod settle_raffle_ticket;
+
+pub use close_whitelist_raffle_registration::*;
+pub use create_whitelist_raffle::*;
+pub use delete_whitelist_raffle::*;
+pub use draw_whitelist_raffle::*;
+pub use reclaim_raffle_ticket::*;
+pub use register_for_whitelist_raffle::*;
+pub use settle_raffle_ticket::*;
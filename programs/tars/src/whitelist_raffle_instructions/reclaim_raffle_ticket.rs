@@ -0,0 +1,23 @@
+use crate::whitelist_raffle::*;
+use crate::{cmp_pubkeys, TarsError};
+use anchor_lang::prelude::*;
+
+/// Close a ticket left unsettled when its raffle was closed, returning the
+/// deposit to the registrant.
+#[derive(Accounts)]
+pub struct ReclaimRaffleTicket<'info> {
+    #[account(mut, has_one = registrant, close = registrant)]
+    raffle_ticket: Account<'info, RaffleTicket>,
+    /// CHECK: only checked to be closed
+    #[account(constraint = cmp_pubkeys(&raffle_ticket.raffle, &whitelist_raffle.key()) @ TarsError::PublicKeyMismatch)]
+    whitelist_raffle: UncheckedAccount<'info>,
+    #[account(mut)]
+    registrant: Signer<'info>,
+}
+
+pub fn handle_reclaim_raffle_ticket(ctx: Context<ReclaimRaffleTicket>) -> Result<()> {
+    if !ctx.accounts.whitelist_raffle.data_is_empty() {
+        return err!(TarsError::RaffleNotClosed);
+    }
+    Ok(())
+}
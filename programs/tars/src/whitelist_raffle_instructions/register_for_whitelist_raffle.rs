@@ -0,0 +1,56 @@
+use crate::whitelist_raffle::*;
+use crate::TarsError;
+use anchor_lang::prelude::*;
+use common::*;
+use solana_program::{program::invoke, system_instruction};
+
+/// Register a wallet for a whitelist raffle, locking the raffle deposit in its ticket.
+#[derive(Accounts)]
+pub struct RegisterForWhitelistRaffle<'info> {
+    #[account(mut)]
+    whitelist_raffle: Account<'info, WhitelistRaffle>,
+    #[account(init,
+        payer = registrant,
+        space = 8 + RaffleTicket::MAX_SERIALIZED_SIZE,
+        seeds = [b"raffle-ticket".as_ref(), whitelist_raffle.key().as_ref(), registrant.key().as_ref()],
+        bump
+    )]
+    raffle_ticket: Account<'info, RaffleTicket>,
+    #[account(mut)]
+    registrant: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+pub fn handle_register_for_whitelist_raffle(
+    ctx: Context<RegisterForWhitelistRaffle>,
+) -> Result<()> {
+    let whitelist_raffle = &mut ctx.accounts.whitelist_raffle;
+    let now = Clock::get()?.unix_timestamp;
+    if now < whitelist_raffle.registration_start_time as i64
+        || now > whitelist_raffle.registration_end_time as i64
+    {
+        return err!(TarsError::RaffleRegistrationClosed);
+    }
+
+    if whitelist_raffle.deposit > 0 {
+        invoke(
+            &system_instruction::transfer(
+                &ctx.accounts.registrant.key(),
+                &ctx.accounts.raffle_ticket.key(),
+                whitelist_raffle.deposit,
+            ),
+            &[
+                ctx.accounts.registrant.to_account_info(),
+                ctx.accounts.raffle_ticket.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+    }
+
+    let raffle_ticket = &mut ctx.accounts.raffle_ticket;
+    raffle_ticket.raffle = whitelist_raffle.key();
+    raffle_ticket.registrant = ctx.accounts.registrant.key();
+    raffle_ticket.index = whitelist_raffle.number_of_registrations;
+    whitelist_raffle.number_of_registrations.try_add_assign(1)?;
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use crate::wallet_whitelist::*;
+use crate::whitelist_config::*;
+use crate::whitelist_raffle::*;
+use crate::{assert_owned_by, cmp_pubkeys, TarsError};
+use anchor_lang::prelude::*;
+use common::*;
+use mpl_token_metadata::utils::create_or_allocate_account_raw;
+
+/// Settle a raffle ticket. Winners get a `WalletWhitelist`, or the spots of the
+/// raffle added to the one they already have for the same tier, and every
+/// ticket is closed so the registrant gets the deposit back. Winners already
+/// whitelisted for another tier can't settle and reclaim the ticket once the
+/// raffle is closed.
+#[derive(Accounts)]
+pub struct SettleRaffleTicket<'info> {
+    #[account(mut)]
+    whitelist_raffle: Account<'info, WhitelistRaffle>,
+    #[account(mut,
+        has_one = registrant,
+        constraint = cmp_pubkeys(&raffle_ticket.raffle, &whitelist_raffle.key()) @ TarsError::PublicKeyMismatch,
+        close = registrant
+    )]
+    raffle_ticket: Account<'info, RaffleTicket>,
+    #[account(constraint = cmp_pubkeys(&whitelist_config.tars_creator, &whitelist_raffle.tars_creator) @ TarsError::PublicKeyMismatch)]
+    whitelist_config: Account<'info, WhitelistConfig>,
+    /// CHECK: only created or updated for winners, derivation checked in handler
+    #[account(mut)]
+    wallet_whitelist: UncheckedAccount<'info>,
+    #[account(mut)]
+    registrant: Signer<'info>,
+    system_program: Program<'info, System>,
+    rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_settle_raffle_ticket(ctx: Context<SettleRaffleTicket>) -> Result<()> {
+    let whitelist_raffle = &mut ctx.accounts.whitelist_raffle;
+    let registrant = &ctx.accounts.registrant;
+    let registrant_key = registrant.key();
+    let tars_creator_key = whitelist_raffle.tars_creator;
+
+    if whitelist_raffle.is_winner(ctx.accounts.raffle_ticket.index)? {
+        let wallet_whitelist_info = ctx.accounts.wallet_whitelist.to_account_info();
+        let seeds = [
+            b"wallet-whitelist".as_ref(),
+            registrant_key.as_ref(),
+            tars_creator_key.as_ref(),
+        ];
+        let (wallet_whitelist_key, bump) = Pubkey::find_program_address(&seeds, &crate::id());
+        if !cmp_pubkeys(&wallet_whitelist_key, wallet_whitelist_info.key) {
+            return err!(TarsError::DerivedKeyInvalid);
+        }
+        if !wallet_whitelist_info.data_is_empty() {
+            assert_owned_by(&wallet_whitelist_info, &crate::id())?;
+            let mut wallet_whitelist =
+                WalletWhitelist::try_deserialize(&mut &wallet_whitelist_info.data.borrow()[..])?;
+            // the spots are minted at the price of the account's tier
+            if wallet_whitelist.whitelist_type != whitelist_raffle.whitelist_type {
+                return err!(TarsError::WhitelistTierMismatch);
+            }
+            wallet_whitelist
+                .number_of_whitelist_spots_per_user
+                .try_add_assign(whitelist_raffle.whitelist_type.spots())?;
+            let mut data_ref: &mut [u8] = &mut wallet_whitelist_info.try_borrow_mut_data()?;
+            wallet_whitelist.try_serialize(&mut data_ref)?;
+        } else {
+            create_or_allocate_account_raw(
+                crate::id(),
+                &wallet_whitelist_info,
+                &ctx.accounts.rent.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &registrant.to_account_info(),
+                8 + WalletWhitelist::MAX_SERIALIZED_SIZE,
+                &[
+                    b"wallet-whitelist".as_ref(),
+                    registrant_key.as_ref(),
+                    tars_creator_key.as_ref(),
+                    &[bump],
+                ],
+            )?;
+
+            let mut wallet_whitelist = WalletWhitelist::new(
+                tars_creator_key,
+                registrant_key,
+                whitelist_raffle.whitelist_type,
+                &ctx.accounts.whitelist_config,
+            )?;
+            wallet_whitelist.funder = registrant_key;
+            let mut data_ref: &mut [u8] = &mut wallet_whitelist_info.try_borrow_mut_data()?;
+            wallet_whitelist.try_serialize(&mut data_ref)?;
+        }
+    }
+
+    whitelist_raffle
+        .number_of_settled_tickets
+        .try_add_assign(1)?;
+    Ok(())
+}
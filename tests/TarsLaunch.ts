@@ -1,16 +1,597 @@
 import * as anchor from "@project-serum/anchor";
-import { Program } from "@project-serum/anchor";
-import { TarsLaunch } from "../target/types/tars_launch";
+import { BN, Program } from "@project-serum/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { expect } from "chai";
+import { createHash } from "crypto";
+import { Tars } from "../target/types/tars";
 
-describe("TarsLaunch", () => {
-  // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+const TARS_CREATOR_WALLET = new PublicKey(
+  "Bi4UpEtKxnHwCw7b9xkMCouGT6xLNm8nixs2fTmxTevs"
+);
+// Over-estimates CONFIG_ARRAY_START, initialize only needs at least the space
+const TARS_HEADER_SPACE = 2048;
 
-  const program = anchor.workspace.TarsLaunch as Program<TarsLaunch>;
+const template = {
+  namePrefix: "Tars #",
+  uriPrefix: "https://arweave.net/",
+  nameSuffixLength: 4,
+  uriSuffixLength: 8,
+};
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+describe("tars", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Tars as Program<Tars>;
+  const authority = provider.wallet.publicKey;
+
+  const tarsData = (overrides: object = {}) => ({
+    uuid: "000000",
+    price: new BN(1_000_000),
+    symbol: "TARS",
+    sellerFeeBasisPoints: 500,
+    maxSupply: new BN(0),
+    isMutable: true,
+    retainAuthority: true,
+    goLiveDate: null,
+    endSettings: null,
+    creators: [{ address: authority, verified: false, share: 100 }],
+    hiddenSettings: null,
+    whitelistMintSettings: null,
+    itemsAvailable: new BN(4),
+    gatekeeper: null,
+    collectionGate: null,
+    burnToMint: null,
+    holderDiscounts: [],
+    authorizedSigner: null,
+    rateLimit: null,
+    configLineTemplate: template,
+    configPages: null,
+    ...overrides,
+  });
+
+  const createTars = async (data: ReturnType<typeof tarsData>) => {
+    const tars = Keypair.generate();
+    const items = data.itemsAvailable.toNumber();
+    const space =
+      TARS_HEADER_SPACE + 4 + items * 12 + 8 + 2 * (Math.floor(items / 8) + 1);
+    await program.methods
+      .initializeTars(data)
+      .accounts({
+        tars: tars.publicKey,
+        wallet: authority,
+        authority,
+        payer: authority,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .preInstructions([
+        SystemProgram.createAccount({
+          fromPubkey: authority,
+          newAccountPubkey: tars.publicKey,
+          space,
+          lamports:
+            await provider.connection.getMinimumBalanceForRentExemption(space),
+          programId: program.programId,
+        }),
+      ])
+      .signers([tars])
+      .rpc();
+    return tars.publicKey;
+  };
+
+  const pda = async (seeds: (Buffer | Uint8Array)[]) =>
+    (await PublicKey.findProgramAddress(seeds, program.programId))[0];
+
+  const configLines = (start: number, count: number) =>
+    Array.from({ length: count }, (_, i) => ({
+      name: `${start + i + 1}`,
+      uri: `tars-${start + i + 1}`,
+    }));
+
+  const fundedKeypair = async () => {
+    const keypair = Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(keypair.publicKey, 1e9)
+    );
+    return keypair;
+  };
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      expect(`${err}`).to.include(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  // Stored lines only keep the suffixes, padded with zeroes to their length
+  const storedLine = (line: { name: string; uri: string }) => {
+    const bytes = Buffer.alloc(
+      template.nameSuffixLength + template.uriSuffixLength
+    );
+    bytes.write(line.name, 0);
+    bytes.write(line.uri, template.nameSuffixLength);
+    return bytes;
+  };
+
+  it("locks templated config lines and hashes them into a manifest", async () => {
+    const tars = await createTars(tarsData());
+    await program.methods
+      .addConfigLines(0, configLines(0, 4))
+      .accounts({ tars, authority })
+      .rpc();
+    await program.methods.lockConfig().accounts({ tars, authority }).rpc();
+    expect((await program.account.tars.fetch(tars)).features).to.equal(0b1000);
+    await expectError(
+      program.methods
+        .removeConfigLines(0, 1)
+        .accounts({ tars, authority })
+        .rpc(),
+      "ConfigLocked"
+    );
+
+    // Anyone can create the manifest of a locked tars
+    const payer = await fundedKeypair();
+    const configManifest = await pda([
+      Buffer.from("config-manifest"),
+      tars.toBuffer(),
+    ]);
+    await program.methods
+      .createConfigManifest()
+      .accounts({
+        tars,
+        configManifest,
+        payer: payer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([payer])
+      .rpc();
+    await program.methods
+      .hashConfigLines(3)
+      .accounts({ tars, configManifest })
+      .rpc();
+    await program.methods
+      .hashConfigLines(10)
+      .accounts({ tars, configManifest })
+      .rpc();
+
+    const manifest = await program.account.configManifest.fetch(
+      configManifest
+    );
+    expect(manifest.linesHashed.toNumber()).to.equal(4);
+    const serializedTemplate = program.coder.types.encode(
+      "ConfigLineTemplate",
+      template
+    );
+    let expected = createHash("sha256")
+      .update(Buffer.concat([Buffer.from([1]), serializedTemplate]))
+      .digest();
+    for (const line of configLines(0, 4)) {
+      expected = createHash("sha256")
+        .update(Buffer.concat([expected, storedLine(line)]))
+        .digest();
+    }
+    expect(Buffer.from(manifest.hash)).to.deep.equal(expected);
+
+    // A locked tars can grow, new lines are appended and hashed once loaded
+    await program.methods
+      .resizeTars(new BN(6))
+      .accounts({
+        tars,
+        authority,
+        payer: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await expectError(
+      program.methods
+        .addConfigLines(3, configLines(3, 2))
+        .accounts({ tars, authority })
+        .rpc(),
+      "ConfigLocked"
+    );
+    await expectError(
+      program.methods
+        .hashConfigLines(2)
+        .accounts({ tars, configManifest })
+        .rpc(),
+      "TarsNotFullyLoaded"
+    );
+    await program.methods
+      .addConfigLines(4, configLines(4, 2))
+      .accounts({ tars, authority })
+      .rpc();
+    await program.methods
+      .hashConfigLines(2)
+      .accounts({ tars, configManifest })
+      .rpc();
+    expect(
+      (
+        await program.account.configManifest.fetch(configManifest)
+      ).linesHashed.toNumber()
+    ).to.equal(6);
+
+    await expectError(
+      program.methods
+        .resizeTars(new BN(5))
+        .accounts({
+          tars,
+          authority,
+          payer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc(),
+      "ConfigLocked"
+    );
+  });
+
+  it("ignores feature flags in the uuid", async () => {
+    // Positions of the flags that used to turn on config pages, the
+    // blocklist, the config lock and reveals
+    const tars = await createTars(tarsData({ uuid: "011111" }));
+    expect((await program.account.tars.fetch(tars)).features).to.equal(0);
+    await program.methods
+      .addConfigLines(0, configLines(0, 4))
+      .accounts({ tars, authority })
+      .rpc();
+    await program.methods
+      .removeConfigLines(0, 1)
+      .accounts({ tars, authority })
+      .rpc();
+  });
+
+  const createConfigPages = async (tars: PublicKey, pageIndexes: number[]) => {
+    const configPages = [];
+    for (const pageIndex of pageIndexes) {
+      const configPage = await pda([
+        Buffer.from("config-page"),
+        tars.toBuffer(),
+        new BN(pageIndex).toArrayLike(Buffer, "le", 4),
+      ]);
+      await program.methods
+        .createConfigPage(pageIndex)
+        .accounts({
+          tars,
+          authority,
+          configPage,
+          payer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      configPages.push(configPage);
+    }
+    return configPages;
+  };
+
+  // The bitmasks of loaded and minted lines follow the discriminator, the
+  // tars and the page index, line `i` is bit `i % 8` of byte `i / 8`
+  const pageMasks = async (configPage: PublicKey, linesPerPage: number) => {
+    const { data } = await provider.connection.getAccountInfo(configPage);
+    const maskLength = Math.ceil(linesPerPage / 8);
+    return {
+      loaded: data.subarray(44, 44 + maskLength),
+      redeemed: data.subarray(44 + maskLength, 44 + 2 * maskLength),
+    };
+  };
+
+  it("loads config lines into config pages", async () => {
+    const tars = await createTars(
+      tarsData({
+        itemsAvailable: new BN(40),
+        configPages: { linesPerPage: 32 },
+      })
+    );
+    const configPages = await createConfigPages(tars, [0, 1]);
+
+    const remainingAccounts = configPages.map((pubkey) => ({
+      pubkey,
+      isWritable: true,
+      isSigner: false,
+    }));
+    await program.methods
+      .addConfigLines(30, configLines(30, 4))
+      .accounts({ tars, authority })
+      .remainingAccounts(remainingAccounts)
+      .rpc();
+
+    const first = await pageMasks(configPages[0], 32);
+    const second = await pageMasks(configPages[1], 32);
+    expect([...first.loaded]).to.deep.equal([0, 0, 0, 0b1100_0000]);
+    expect([...second.loaded]).to.deep.equal([0b11, 0, 0, 0]);
+    expect([...first.redeemed]).to.deep.equal([0, 0, 0, 0]);
+
+    // The pages hold fewer lines than the tars, it can't be locked yet
+    await expectError(
+      program.methods.lockConfig().accounts({ tars, authority }).rpc(),
+      "TarsNotFullyLoaded"
+    );
+    await program.methods
+      .removeConfigLines(31, 2)
+      .accounts({ tars, authority })
+      .remainingAccounts(remainingAccounts)
+      .rpc();
+    expect([...(await pageMasks(configPages[1], 32)).loaded]).to.deep.equal([
+      0b10, 0, 0, 0,
+    ]);
+  });
+
+  it("sizes config pages for their number of lines", async () => {
+    await expectError(
+      createTars(tarsData({ configPages: { linesPerPage: 0 } })),
+      "InvalidConfigPageSettings"
+    );
+    // Pages must fit in an account the program can create
+    await expectError(
+      createTars(tarsData({ configPages: { linesPerPage: 1000 } })),
+      "InvalidConfigPageSettings"
+    );
+
+    const tars = await createTars(
+      tarsData({
+        itemsAvailable: new BN(700),
+        configPages: { linesPerPage: 500 },
+      })
+    );
+    const configPages = await createConfigPages(tars, [0, 1]);
+    await expectError(
+      createConfigPages(tars, [2]),
+      "IndexGreaterThanLength"
+    );
+    await program.methods
+      .addConfigLines(498, configLines(498, 4))
+      .accounts({ tars, authority })
+      .remainingAccounts(
+        configPages.map((pubkey) => ({
+          pubkey,
+          isWritable: true,
+          isSigner: false,
+        }))
+      )
+      .rpc();
+    const first = await pageMasks(configPages[0], 500);
+    const second = await pageMasks(configPages[1], 500);
+    expect(first.loaded[62]).to.equal(0b1100);
+    expect(second.loaded[0]).to.equal(0b11);
+    // Settings can't change once the pages are laid out
+    await expectError(
+      program.methods
+        .updateTars(
+          tarsData({
+            itemsAvailable: new BN(700),
+            configPages: { linesPerPage: 32 },
+          })
+        )
+        .accounts({ tars, authority, wallet: authority })
+        .rpc(),
+      "CannotChangeConfigPages"
+    );
+  });
+
+  it("commits and closes reveal stages of a hidden settings tars", async () => {
+    const tars = await createTars(
+      tarsData({
+        itemsAvailable: new BN(10),
+        configLineTemplate: null,
+        hiddenSettings: {
+          name: "Tars",
+          uri: "https://arweave.net/hidden",
+          hash: Array(32).fill(0),
+        },
+      })
+    );
+    const revealSchedule = await pda([
+      Buffer.from("reveal-schedule"),
+      tars.toBuffer(),
+    ]);
+    const stage = (endMintNumber: number, unlockTime: number) => ({
+      endMintNumber: new BN(endMintNumber),
+      root: Array(32).fill(endMintNumber),
+      unlockTime: new BN(unlockTime),
+    });
+    const commitStages = (stages: ReturnType<typeof stage>[]) =>
+      program.methods
+        .commitRevealStages(stages)
+        .accounts({
+          tars,
+          authority,
+          revealSchedule,
+          payer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    // Stages must cover every item, in order
+    await expectError(commitStages([stage(5, 0)]), "InvalidRevealStages");
+    await expectError(
+      commitStages([stage(5, 0), stage(3, 0), stage(10, 0)]),
+      "InvalidRevealStages"
+    );
+    await commitStages([stage(5, 100), stage(10, 200)]);
+    const schedule = await program.account.revealSchedule.fetch(revealSchedule);
+    expect(schedule.stages.length).to.equal(2);
+    // Reveal and staged reveal bits
+    expect((await program.account.tars.fetch(tars)).features).to.equal(
+      0b110000
+    );
+
+    // A single root can't replace the stages until they are closed
+    await expectError(
+      program.methods
+        .commitReveal(Array(32).fill(1))
+        .accounts({ tars, authority })
+        .rpc(),
+      "RevealScheduleCommitted"
+    );
+    await program.methods
+      .closeRevealSchedule()
+      .accounts({ tars, authority, revealSchedule })
+      .rpc();
+    expect((await program.account.tars.fetch(tars)).features).to.equal(0);
+    await commitStages([stage(10, 100)]);
+  });
+
+  it("sizes the blocklist for its wallets", async () => {
+    const tars = await createTars(tarsData());
+    const blocklist = await pda([Buffer.from("blocklist"), tars.toBuffer()]);
+    await program.methods
+      .createBlocklist(3)
+      .accounts({
+        tars,
+        authority,
+        blocklist,
+        payer: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    const info = await provider.connection.getAccountInfo(blocklist);
+    expect(info.data.length).to.equal(8 + 32 + 4 + 4 + 3 * 32);
+
+    const wallets = () =>
+      [Keypair.generate(), Keypair.generate(), Keypair.generate()].map(
+        (keypair) => keypair.publicKey
+      );
+    await program.methods
+      .addBlockedWallets(wallets())
+      .accounts({ tars, authority, blocklist })
+      .rpc();
+    expect(
+      (await program.account.blocklist.fetch(blocklist)).wallets.length
+    ).to.equal(3);
+    await expectError(
+      program.methods
+        .addBlockedWallets(wallets().slice(0, 1))
+        .accounts({ tars, authority, blocklist })
+        .rpc(),
+      "BlocklistFull"
+    );
+  });
+
+  describe("whitelist raffles", () => {
+    const tier = (start: number) => ({
+      // Overwritten by the program with the tier of its position
+      whitelistType: { null: {} },
+      numberOfWhitelistSpotsTotal: new BN(10),
+      discountedMintPrice: new BN(500_000),
+      whitelistMintStartTime: new BN(start),
+      whitelistMintEndTime: new BN(start + 3600),
+      unusedSpotsToPublic: false,
+    });
+    const schedule = (start: number) => ({
+      wlStartTime4: tier(start),
+      wlStartTime3: tier(start + 60),
+      wlStartTime2: tier(start + 120),
+      wlStartTime1: tier(start + 180),
+    });
+
+    before(function () {
+      // Whitelist configs can only be created by the tars creator wallet
+      if (!authority.equals(TARS_CREATOR_WALLET)) {
+        this.skip();
+      }
+    });
+
+    it("creates a raffle and closes its registration", async () => {
+      const tars = await createTars(tarsData());
+      const whitelistConfig = await pda([
+        Buffer.from("whitelist-config"),
+        authority.toBuffer(),
+      ]);
+      const now = Math.floor(Date.now() / 1000);
+      if (!(await provider.connection.getAccountInfo(whitelistConfig))) {
+        await program.methods
+          .createWhitelistConfig(schedule(now))
+          .accounts({
+            whitelistConfig,
+            tarsCreator: authority,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+      }
+      await program.methods
+        .updateWhitelistConfig(schedule(now + 60))
+        .accounts({ whitelistConfig, tarsCreator: authority })
+        .rpc();
+      const config = await program.account.whitelistConfig.fetch(
+        whitelistConfig
+      );
+      expect(config.whitelistSchedule.wlStartTime1.whitelistType).to.deep.equal(
+        { one: {} }
+      );
+
+      const whitelistRaffle = await pda([
+        Buffer.from("whitelist-raffle"),
+        tars.toBuffer(),
+      ]);
+      const createRaffle = (registrationEnd: number, settlementEnd: number) =>
+        program.methods
+          .createWhitelistRaffle(
+            { one: {} },
+            new BN(now - 60),
+            new BN(registrationEnd),
+            new BN(settlementEnd),
+            new BN(1_000_000),
+            1
+          )
+          .accounts({
+            whitelistRaffle,
+            tars,
+            whitelistConfig,
+            tarsCreator: authority,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+      const registrationEnd = now + 10;
+      await expectError(
+        createRaffle(registrationEnd, registrationEnd - 1),
+        "InvalidRaffleSchedule"
+      );
+      await createRaffle(registrationEnd, now + 3600);
+
+      const registrant = await fundedKeypair();
+      const raffleTicket = await pda([
+        Buffer.from("raffle-ticket"),
+        whitelistRaffle.toBuffer(),
+        registrant.publicKey.toBuffer(),
+      ]);
+      await program.methods
+        .registerForWhitelistRaffle()
+        .accounts({
+          whitelistRaffle,
+          raffleTicket,
+          registrant: registrant.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([registrant])
+        .rpc();
+      await expectError(
+        program.methods
+          .closeWhitelistRaffleRegistration()
+          .accounts({ whitelistRaffle })
+          .rpc(),
+        "RaffleRegistrationOpen"
+      );
+
+      await new Promise((resolve) =>
+        setTimeout(resolve, (registrationEnd + 2) * 1000 - Date.now())
+      );
+      await program.methods
+        .closeWhitelistRaffleRegistration()
+        .accounts({ whitelistRaffle })
+        .rpc();
+      const raffle = await program.account.whitelistRaffle.fetch(
+        whitelistRaffle
+      );
+      expect(raffle.numberOfRegistrations).to.equal(1);
+      expect(raffle.drawSlot).to.not.equal(null);
+      await expectError(
+        program.methods
+          .closeWhitelistRaffleRegistration()
+          .accounts({ whitelistRaffle })
+          .rpc(),
+        "RaffleDrawScheduled"
+      );
+    });
   });
 });